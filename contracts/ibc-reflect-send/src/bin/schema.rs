@@ -2,7 +2,7 @@ use std::env::current_dir;
 
 use cosmwasm_schema::{export_schema, schema_for, write_api};
 
-use ibc_reflect_send::ibc_msg::PacketMsg;
+use ibc_reflect_send::ibc_msg::{BalancesResponse, PacketMsg, WhoAmIResponse};
 use ibc_reflect_send::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
 
 fn main() {
@@ -17,4 +17,6 @@ fn main() {
     let mut out_dir = current_dir().unwrap();
     out_dir.push("schema");
     export_schema(&schema_for!(PacketMsg), &out_dir);
+    export_schema(&schema_for!(WhoAmIResponse), &out_dir);
+    export_schema(&schema_for!(BalancesResponse), &out_dir);
 }
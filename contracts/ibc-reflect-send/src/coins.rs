@@ -0,0 +1,180 @@
+use cosmwasm_std::{Coin, StdError, StdResult};
+
+/// Extension trait to normalize a list of coins, e.g. `balance.normalize()`
+pub trait NormalizeCoins {
+    /// Drops all zero-amount coins, such that an empty balance is always represented
+    /// by an empty vector and emptiness checks behave as expected.
+    fn normalize(self) -> Vec<Coin>;
+}
+
+impl NormalizeCoins for Vec<Coin> {
+    fn normalize(self) -> Vec<Coin> {
+        self.into_iter()
+            .filter(|coin| !coin.amount.is_zero())
+            .collect()
+    }
+}
+
+/// Merges `incoming` into `existing` by denom, summing up the amounts of shared denoms.
+/// Denoms not yet in `existing` are appended in the order they appear in `incoming`.
+///
+/// Returns an overflow error if any sum exceeds the `Uint128` range.
+pub fn add_coins(existing: Vec<Coin>, incoming: Vec<Coin>) -> StdResult<Vec<Coin>> {
+    let mut total = existing;
+    for coin in incoming {
+        match total.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(c) => c.amount = c.amount.checked_add(coin.amount)?,
+            None => total.push(coin),
+        }
+    }
+    Ok(total)
+}
+
+/// Like [`add_coins`] but saturates at `Uint128::MAX` instead of returning an error.
+pub fn saturating_add_coins(existing: Vec<Coin>, incoming: Vec<Coin>) -> Vec<Coin> {
+    let mut total = existing;
+    for coin in incoming {
+        match total.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(c) => c.amount = c.amount.saturating_add(coin.amount),
+            None => total.push(coin),
+        }
+    }
+    total
+}
+
+/// Subtracts `outgoing` from `existing` by denom. Denoms that reach zero are dropped.
+///
+/// Returns an overflow error if an amount exceeds the existing amount, and a generic
+/// error if a denom is not in `existing` at all.
+pub fn sub_coins(existing: Vec<Coin>, outgoing: Vec<Coin>) -> StdResult<Vec<Coin>> {
+    let mut total = existing;
+    for coin in outgoing {
+        match total.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(c) => c.amount = c.amount.checked_sub(coin.amount)?,
+            None => {
+                return Err(StdError::generic_err(format!(
+                    "Cannot subtract {}: denom not in balance",
+                    coin
+                )))
+            }
+        }
+    }
+    Ok(total.normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, OverflowError, OverflowOperation, Uint128};
+
+    #[test]
+    fn normalize_works() {
+        // empty stays empty
+        assert!(Vec::<Coin>::new().normalize().is_empty());
+
+        // zero amounts are dropped
+        assert!(vec![coin(0, "ucosm")].normalize().is_empty());
+        assert!(vec![coin(0, "ucosm"), coin(0, "ustake")]
+            .normalize()
+            .is_empty());
+
+        // non-zero amounts are kept in order
+        assert_eq!(
+            vec![coin(12, "ucosm"), coin(0, "uatom"), coin(3, "ustake")].normalize(),
+            vec![coin(12, "ucosm"), coin(3, "ustake")]
+        );
+    }
+
+    #[test]
+    fn add_coins_merges_disjoint_denoms() {
+        let sum = add_coins(vec![coin(12, "ucosm")], vec![coin(3, "ustake")]).unwrap();
+        assert_eq!(sum, vec![coin(12, "ucosm"), coin(3, "ustake")]);
+
+        let sum = add_coins(vec![], vec![coin(3, "ustake")]).unwrap();
+        assert_eq!(sum, vec![coin(3, "ustake")]);
+
+        let sum = add_coins(vec![coin(12, "ucosm")], vec![]).unwrap();
+        assert_eq!(sum, vec![coin(12, "ucosm")]);
+    }
+
+    #[test]
+    fn add_coins_sums_shared_denom() {
+        let sum = add_coins(
+            vec![coin(12, "ucosm"), coin(5, "uatom")],
+            vec![coin(3, "uatom"), coin(7, "ucosm"), coin(1, "ustake")],
+        )
+        .unwrap();
+        assert_eq!(
+            sum,
+            vec![coin(19, "ucosm"), coin(8, "uatom"), coin(1, "ustake")]
+        );
+    }
+
+    #[test]
+    fn add_coins_errors_on_overflow() {
+        let err = add_coins(vec![coin(u128::MAX, "ucosm")], vec![coin(1, "ucosm")]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::overflow(OverflowError::new(
+                OverflowOperation::Add,
+                Uint128::MAX,
+                Uint128::one()
+            ))
+        );
+
+        let sum = saturating_add_coins(vec![coin(u128::MAX, "ucosm")], vec![coin(1, "ucosm")]);
+        assert_eq!(sum, vec![coin(u128::MAX, "ucosm")]);
+    }
+
+    #[test]
+    fn sub_coins_works() {
+        let rest = sub_coins(
+            vec![coin(12, "ucosm"), coin(5, "uatom"), coin(1, "ustake")],
+            vec![coin(3, "uatom"), coin(7, "ucosm")],
+        )
+        .unwrap();
+        assert_eq!(
+            rest,
+            vec![coin(5, "ucosm"), coin(2, "uatom"), coin(1, "ustake")]
+        );
+
+        // denoms reaching zero are dropped
+        let rest = sub_coins(
+            vec![coin(12, "ucosm"), coin(5, "uatom")],
+            vec![coin(12, "ucosm")],
+        )
+        .unwrap();
+        assert_eq!(rest, vec![coin(5, "uatom")]);
+
+        let rest = sub_coins(vec![coin(12, "ucosm")], vec![]).unwrap();
+        assert_eq!(rest, vec![coin(12, "ucosm")]);
+    }
+
+    #[test]
+    fn sub_coins_errors_on_underflow() {
+        let err = sub_coins(vec![coin(12, "ucosm")], vec![coin(13, "ucosm")]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::overflow(OverflowError::new(
+                OverflowOperation::Sub,
+                Uint128::new(12),
+                Uint128::new(13)
+            ))
+        );
+    }
+
+    #[test]
+    fn sub_coins_errors_on_unknown_denom() {
+        let err = sub_coins(vec![coin(12, "ucosm")], vec![coin(1, "uatom")]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Cannot subtract 1uatom: denom not in balance")
+        );
+
+        let err = sub_coins(vec![], vec![coin(1, "uatom")]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Cannot subtract 1uatom: denom not in balance")
+        );
+    }
+}
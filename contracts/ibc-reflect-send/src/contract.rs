@@ -1,63 +1,201 @@
+use std::collections::BTreeMap;
+
 use cosmwasm_std::{
-    entry_point, to_binary, CosmosMsg, Deps, DepsMut, Env, IbcMsg, MessageInfo, Order,
-    QueryResponse, Response, StdError, StdResult,
+    entry_point, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, Event,
+    IbcMsg, IbcTimeout, MessageInfo, Order, QueryResponse, Record, Response, StdError, StdResult,
+    Storage, WasmMsg,
 };
 
-use crate::ibc::PACKET_LIFETIME;
+use cosmwasm_storage::{paginate, DEFAULT_LIMIT, MAX_LIMIT};
+
+use crate::coins::{add_coins, sub_coins, NormalizeCoins};
+use crate::errors::ContractError;
+#[cfg(feature = "test-utils")]
+use crate::ibc::acknowledge_packet;
+use crate::ibc::packet_timeout;
 use crate::ibc_msg::PacketMsg;
 use crate::msg::{
-    AccountInfo, AccountResponse, AdminResponse, ExecuteMsg, InstantiateMsg, ListAccountsResponse,
-    QueryMsg,
+    AccountByRemoteResponse, AccountInfo, AccountResponse, AccountSummary, AdminResponse,
+    ExecuteMsg, InstantiateMsg, ListAccountSummariesResponse, ListAccountsResponse,
+    PendingAccountsResponse, PreviewDispatchResponse, QueryMsg, TotalBalancesResponse,
 };
-use crate::state::{accounts, accounts_read, config, config_read, Config};
+use crate::state::{
+    accounts, accounts_read, channel_pair, channel_pair_read, config, config_read, drain_nonce,
+    load_account_or_err, pending_drain, pending_drain_read, remote_index_read, sorted_index_key,
+    sorted_index_read, AccountData, ChannelPair, Config, PendingDrain, DEFAULT_MAX_MSGS_PER_PACKET,
+    DEFAULT_MAX_WHO_AM_I_RETRIES, DEFAULT_TIMEOUT_BLOCKS,
+};
+
+/// number of blocks between proposing and executing a drain
+pub const DRAIN_DELAY_BLOCKS: u64 = 100;
+/// maximum number of channels SendMsgsMulti sends to at once
+pub const MAX_CHANNELS_PER_BATCH: usize = 10;
 
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> StdResult<Response> {
     // we store the reflect_id for creating accounts later
-    let cfg = Config { admin: info.sender };
+    let cfg = Config {
+        admin: Some(info.sender),
+        allow_remote_addr_change: msg.allow_remote_addr_change,
+        counterparty_pubkey: msg.counterparty_pubkey,
+        max_who_am_i_retries: msg
+            .max_who_am_i_retries
+            .unwrap_or(DEFAULT_MAX_WHO_AM_I_RETRIES),
+        allowed_denoms: msg.allowed_denoms,
+        timeout_mode: msg.timeout_mode.unwrap_or_default(),
+        timeout_blocks: msg.timeout_blocks.unwrap_or(DEFAULT_TIMEOUT_BLOCKS),
+        timeout_revision: msg.timeout_revision.unwrap_or_default(),
+        max_msgs_per_packet: msg
+            .max_msgs_per_packet
+            .unwrap_or(DEFAULT_MAX_MSGS_PER_PACKET),
+        optimistic_balance_updates: msg.optimistic_balance_updates,
+        expected_port_prefix: msg.expected_port_prefix,
+    };
     config(deps.storage).save(&cfg)?;
 
     Ok(Response::new().add_attribute("action", "instantiate"))
 }
 
 #[entry_point]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::UpdateAdmin { admin } => handle_update_admin(deps, info, admin),
+        ExecuteMsg::RenounceAdmin {} => handle_renounce_admin(deps, info),
+        ExecuteMsg::UpdateAllowedDenoms { allowed_denoms } => {
+            handle_update_allowed_denoms(deps, info, allowed_denoms)
+        }
+        ExecuteMsg::UpdateExpectedPortPrefix {
+            expected_port_prefix,
+        } => handle_update_expected_port_prefix(deps, info, expected_port_prefix),
+        ExecuteMsg::UpdateMaxMsgsPerPacket {
+            max_msgs_per_packet,
+        } => handle_update_max_msgs_per_packet(deps, info, max_msgs_per_packet),
         ExecuteMsg::SendMsgs { channel_id, msgs } => {
             handle_send_msgs(deps, env, info, channel_id, msgs)
         }
+        ExecuteMsg::SendMsgsMulti { channel_ids, msgs } => {
+            handle_send_msgs_multi(deps, env, info, channel_ids, msgs)
+        }
         ExecuteMsg::CheckRemoteBalance { channel_id } => {
             handle_check_remote_balance(deps, env, info, channel_id)
         }
+        ExecuteMsg::RefreshAll { start_after, limit } => {
+            handle_refresh_all(deps, env, info, start_after, limit)
+        }
         ExecuteMsg::SendFunds {
             reflect_channel_id,
             transfer_channel_id,
         } => handle_send_funds(deps, env, info, reflect_channel_id, transfer_channel_id),
+        ExecuteMsg::SetChannelPair {
+            reflect_channel_id,
+            transfer_channel_id,
+        } => handle_set_channel_pair(deps, info, reflect_channel_id, transfer_channel_id),
+        ExecuteMsg::SendFundsDefault {} => handle_send_funds_default(deps, env, info),
+        ExecuteMsg::Withdraw { to_address, amount } => {
+            handle_withdraw(deps, info, to_address, amount)
+        }
+        ExecuteMsg::ProposeDrain {} => handle_propose_drain(deps, env, info),
+        ExecuteMsg::ExecuteDrain { nonce } => handle_execute_drain(deps, env, info, nonce),
+        #[cfg(feature = "test-utils")]
+        ExecuteMsg::TestInjectAck {
+            channel_id,
+            packet,
+            ack,
+        } => handle_test_inject_ack(deps, env, info, channel_id, packet, ack),
+    }
+}
+
+/// Fails with `ContractError::Unauthorized` unless `sender` is the admin.
+/// Always fails once the admin was renounced.
+pub fn ensure_admin(cfg: &Config, sender: &Addr) -> Result<(), ContractError> {
+    if cfg.admin.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
     }
+    Ok(())
 }
 
 pub fn handle_update_admin(
     deps: DepsMut,
     info: MessageInfo,
     new_admin: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // auth check
     let mut cfg = config(deps.storage).load()?;
-    if info.sender != cfg.admin {
-        return Err(StdError::generic_err("Only admin may set new admin"));
-    }
-    cfg.admin = deps.api.addr_validate(&new_admin)?;
+    ensure_admin(&cfg, &info.sender)?;
+    let new_admin = deps.api.addr_validate(&new_admin)?;
+    cfg.admin = Some(new_admin.clone());
     config(deps.storage).save(&cfg)?;
+    // a drain proposed by the old admin must not be executable by the new one
+    pending_drain(deps.storage).remove();
 
     Ok(Response::new()
         .add_attribute("action", "handle_update_admin")
-        .add_attribute("new_admin", cfg.admin))
+        .add_attribute("new_admin", new_admin))
+}
+
+/// Removes the admin for good, which disables all admin-only messages
+pub fn handle_renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    // auth check
+    let mut cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+    cfg.admin = None;
+    config(deps.storage).save(&cfg)?;
+
+    Ok(Response::new().add_attribute("action", "handle_renounce_admin"))
+}
+
+pub fn handle_update_allowed_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    allowed_denoms: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    // auth check
+    let mut cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+    cfg.allowed_denoms = allowed_denoms;
+    config(deps.storage).save(&cfg)?;
+
+    Ok(Response::new().add_attribute("action", "handle_update_allowed_denoms"))
+}
+
+pub fn handle_update_expected_port_prefix(
+    deps: DepsMut,
+    info: MessageInfo,
+    expected_port_prefix: Option<String>,
+) -> Result<Response, ContractError> {
+    // auth check
+    let mut cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+    cfg.expected_port_prefix = expected_port_prefix;
+    config(deps.storage).save(&cfg)?;
+
+    Ok(Response::new().add_attribute("action", "handle_update_expected_port_prefix"))
+}
+
+pub fn handle_update_max_msgs_per_packet(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_msgs_per_packet: u32,
+) -> Result<Response, ContractError> {
+    // auth check
+    let mut cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+    cfg.max_msgs_per_packet = max_msgs_per_packet;
+    config(deps.storage).save(&cfg)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "handle_update_max_msgs_per_packet")
+        .add_attribute("max_msgs_per_packet", max_msgs_per_packet.to_string()))
 }
 
 pub fn handle_send_msgs(
@@ -66,54 +204,245 @@ pub fn handle_send_msgs(
     info: MessageInfo,
     channel_id: String,
     msgs: Vec<CosmosMsg>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // auth check
     let cfg = config(deps.storage).load()?;
-    if info.sender != cfg.admin {
-        return Err(StdError::generic_err("Only admin may send messages"));
-    }
-    // ensure the channel exists (not found if not registered)
-    accounts(deps.storage).load(channel_id.as_bytes())?;
+    ensure_admin(&cfg, &info.sender)?;
 
-    // construct a packet to send
-    let packet = PacketMsg::Dispatch { msgs };
+    let event = Event::new("ibc_dispatch")
+        .add_attribute("channel_id", &channel_id)
+        .add_attribute("msgs", msgs.len().to_string());
+    if cfg.optimistic_balance_updates {
+        subtract_bank_sends(deps.storage, &channel_id, &msgs)?;
+    }
+    let (data, timeout) = dispatch_packet(deps.storage, &env, &channel_id, msgs)?;
     let msg = IbcMsg::SendPacket {
         channel_id,
-        data: to_binary(&packet)?,
-        timeout: env.block.time.plus_seconds(PACKET_LIFETIME).into(),
+        data,
+        timeout,
     };
 
     let res = Response::new()
         .add_message(msg)
-        .add_attribute("action", "handle_send_msgs");
+        .add_attribute("action", "handle_send_msgs")
+        .add_event(event);
     Ok(res)
 }
 
+pub fn handle_send_msgs_multi(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel_ids: Vec<String>,
+    msgs: Vec<CosmosMsg>,
+) -> Result<Response, ContractError> {
+    // auth check
+    let cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+    if channel_ids.is_empty() || channel_ids.len() > MAX_CHANNELS_PER_BATCH {
+        return Err(ContractError::InvalidChannelCount {
+            count: channel_ids.len(),
+            max: MAX_CHANNELS_PER_BATCH,
+        });
+    }
+
+    // all channels must exist before anything is sent
+    for channel_id in &channel_ids {
+        load_account_or_err(deps.storage, channel_id)?;
+    }
+    if cfg.optimistic_balance_updates {
+        for channel_id in &channel_ids {
+            subtract_bank_sends(deps.storage, channel_id, &msgs)?;
+        }
+    }
+
+    let msgs_count = msgs.len().to_string();
+    let (data, timeout) = build_dispatch_packet(deps.storage, &env, msgs)?;
+    let mut res = Response::new().add_attribute("action", "handle_send_msgs_multi");
+    for channel_id in channel_ids {
+        let event = Event::new("ibc_dispatch")
+            .add_attribute("channel_id", &channel_id)
+            .add_attribute("msgs", &msgs_count);
+        res = res
+            .add_message(IbcMsg::SendPacket {
+                channel_id,
+                data: data.clone(),
+                timeout: timeout.clone(),
+            })
+            .add_event(event);
+    }
+    Ok(res)
+}
+
+/// Subtracts the amounts of all bank sends in `msgs` from the cached remote balance
+/// of `channel_id`, see `Config::optimistic_balance_updates`.
+/// Denoms not in the cached balance are skipped.
+fn subtract_bank_sends(
+    storage: &mut dyn Storage,
+    channel_id: &str,
+    msgs: &[CosmosMsg],
+) -> Result<(), ContractError> {
+    let mut outgoing = vec![];
+    for msg in msgs {
+        if let CosmosMsg::Bank(BankMsg::Send { amount, .. }) = msg {
+            outgoing = add_coins(outgoing, amount.clone())?;
+        }
+    }
+    let mut account = load_account_or_err(storage, channel_id)?;
+    outgoing.retain(|coin| {
+        account
+            .remote_balance
+            .iter()
+            .any(|cached| cached.denom == coin.denom)
+    });
+    if outgoing.is_empty() {
+        return Ok(());
+    }
+    account.remote_balance = sub_coins(account.remote_balance, outgoing)?;
+    accounts(storage).save(channel_id.as_bytes(), &account)?;
+    Ok(())
+}
+
+/// Builds the packet data and timeout for dispatching `msgs` over `channel_id`.
+/// Shared by `SendMsgs` and the `PreviewDispatch` query.
+fn dispatch_packet(
+    storage: &dyn Storage,
+    env: &Env,
+    channel_id: &str,
+    msgs: Vec<CosmosMsg>,
+) -> Result<(Binary, IbcTimeout), ContractError> {
+    // ensure the channel exists (not found if not registered)
+    load_account_or_err(storage, channel_id)?;
+    build_dispatch_packet(storage, env, msgs)
+}
+
+/// Like `dispatch_packet` but without checking a channel, such that the packet
+/// can be sent over multiple channels.
+fn build_dispatch_packet(
+    storage: &dyn Storage,
+    env: &Env,
+    msgs: Vec<CosmosMsg>,
+) -> Result<(Binary, IbcTimeout), ContractError> {
+    let cfg = config_read(storage).load()?;
+    if msgs.len() > cfg.max_msgs_per_packet as usize {
+        return Err(ContractError::TooManyMsgs {
+            count: msgs.len(),
+            max: cfg.max_msgs_per_packet,
+        });
+    }
+    validate_msgs(&msgs)?;
+
+    // construct a packet to send
+    let packet = PacketMsg::Dispatch { msgs };
+    Ok((to_binary(&packet)?, packet_timeout(env, &cfg)))
+}
+
+/// Rejects obviously malformed messages before they cost a round trip to the remote chain.
+/// This is a sanity check only, the remote chain still validates the messages fully.
+pub fn validate_msgs(msgs: &[CosmosMsg]) -> StdResult<()> {
+    for (i, msg) in msgs.iter().enumerate() {
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                if to_address.is_empty() {
+                    return Err(StdError::generic_err(format!(
+                        "Message {}: bank recipient must not be empty",
+                        i
+                    )));
+                }
+                if amount.is_empty() || amount.iter().any(|coin| coin.amount.is_zero()) {
+                    return Err(StdError::generic_err(format!(
+                        "Message {}: bank send amount must not be zero",
+                        i
+                    )));
+                }
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+                if contract_addr.is_empty() {
+                    return Err(StdError::generic_err(format!(
+                        "Message {}: contract address must not be empty",
+                        i
+                    )));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 pub fn handle_check_remote_balance(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     channel_id: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // auth check
     let cfg = config(deps.storage).load()?;
-    if info.sender != cfg.admin {
-        return Err(StdError::generic_err("Only admin may send messages"));
-    }
+    ensure_admin(&cfg, &info.sender)?;
     // ensure the channel exists (not found if not registered)
-    accounts(deps.storage).load(channel_id.as_bytes())?;
+    load_account_or_err(deps.storage, &channel_id)?;
 
     // construct a packet to send
     let packet = PacketMsg::Balances {};
+    let event = Event::new("ibc_check_balance").add_attribute("channel_id", &channel_id);
     let msg = IbcMsg::SendPacket {
         channel_id,
         data: to_binary(&packet)?,
-        timeout: env.block.time.plus_seconds(PACKET_LIFETIME).into(),
+        timeout: packet_timeout(&env, &cfg),
     };
 
     let res = Response::new()
         .add_message(msg)
-        .add_attribute("action", "handle_check_remote_balance");
+        .add_attribute("action", "handle_check_remote_balance")
+        .add_event(event);
+    Ok(res)
+}
+
+pub fn handle_refresh_all(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    // auth check
+    let cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+
+    let start_after = start_after.as_ref().map(|channel_id| channel_id.as_bytes());
+    let channel_ids: Vec<String> = paginate(
+        &accounts_read(deps.storage),
+        start_after,
+        limit,
+        Order::Ascending,
+    )?
+    .into_iter()
+    .map(|record| decode_account(record).map(|(channel_id, _)| channel_id))
+    .collect::<StdResult<_>>()?;
+    // same limit as applied by paginate
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let next = match channel_ids.last() {
+        Some(last) if channel_ids.len() == limit => Some(last.clone()),
+        _ => None,
+    };
+
+    // the acks are handled in `ibc_packet_ack` like the WhoAmI sent on connect
+    let data = to_binary(&PacketMsg::WhoAmI {})?;
+    let timeout = packet_timeout(&env, &cfg);
+    let mut res = Response::new().add_attribute("action", "handle_refresh_all");
+    if let Some(next) = next {
+        res = res.add_attribute("next", next);
+    }
+    for channel_id in channel_ids {
+        let event = Event::new("ibc_who_am_i").add_attribute("channel_id", &channel_id);
+        res = res
+            .add_message(IbcMsg::SendPacket {
+                channel_id,
+                data: data.clone(),
+                timeout: timeout.clone(),
+            })
+            .add_event(event);
+    }
     Ok(res)
 }
 
@@ -123,99 +452,1811 @@ pub fn handle_send_funds(
     mut info: MessageInfo,
     reflect_channel_id: String,
     transfer_channel_id: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // intentionally no auth check
 
     // require some funds
     let amount = match info.funds.pop() {
         Some(coin) => coin,
-        None => {
-            return Err(StdError::generic_err(
-                "you must send the coins you wish to ibc transfer",
-            ))
-        }
+        None => return Err(ContractError::NoFunds {}),
     };
     // if there are any more coins, reject the message
     if !info.funds.is_empty() {
-        return Err(StdError::generic_err("you can only ibc transfer one coin"));
+        return Err(ContractError::MultipleDenoms {});
+    }
+    // an empty transfer would only waste relayer fees
+    if amount.amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let cfg = config_read(deps.storage).load()?;
+    if !cfg.is_denom_allowed(&amount.denom) {
+        return Err(ContractError::DenomNotAllowed {
+            denom: amount.denom,
+        });
     }
 
     // load remote account
-    let data = accounts(deps.storage).load(reflect_channel_id.as_bytes())?;
+    let data = match accounts_read(deps.storage).may_load(reflect_channel_id.as_bytes())? {
+        Some(data) => data,
+        None => {
+            return Err(ContractError::ReflectChannelNotRegistered {
+                channel_id: reflect_channel_id,
+            })
+        }
+    };
     let remote_addr = match data.remote_addr {
         Some(addr) => addr,
         None => {
-            return Err(StdError::generic_err(
-                "We don't have the remote address for this channel",
-            ))
+            return Err(ContractError::NoRemoteAddr {
+                channel_id: reflect_channel_id,
+            })
         }
     };
 
+    // The sent coins are part of our balance already, unless an earlier message
+    // of the same transaction spent them. Better fail here than in the transfer.
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address.as_str(), &amount.denom)?;
+    if balance.amount < amount.amount {
+        return Err(ContractError::InsufficientBalance {
+            denom: amount.denom,
+            balance: balance.amount,
+            required: amount.amount,
+        });
+    }
+
+    // The cached remote balance is not credited in optimistic mode: the coin arrives as an
+    // `ibc/` voucher denom derived from the counterparty's channel, which we do not know here,
+    // and the transfer might still time out and be refunded. The next Balances update picks it
+    // up, and bank sends of denoms not in the cache are not checked until then.
+
     // construct a packet to send
+    let event = Event::new("ibc_transfer")
+        .add_attribute("reflect_channel_id", reflect_channel_id)
+        .add_attribute("transfer_channel_id", &transfer_channel_id)
+        .add_attribute("to_address", &remote_addr)
+        .add_attribute("amount", amount.to_string());
     let msg = IbcMsg::Transfer {
         channel_id: transfer_channel_id,
         to_address: remote_addr,
         amount,
-        timeout: env.block.time.plus_seconds(PACKET_LIFETIME).into(),
+        timeout: packet_timeout(&env, &cfg),
     };
 
     let res = Response::new()
         .add_message(msg)
-        .add_attribute("action", "handle_send_funds");
+        .add_attribute("action", "handle_send_funds")
+        .add_event(event);
+    Ok(res)
+}
+
+pub fn handle_set_channel_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    reflect_channel_id: String,
+    transfer_channel_id: String,
+) -> Result<Response, ContractError> {
+    // auth check
+    let cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+    let pair = ChannelPair {
+        reflect_channel_id,
+        transfer_channel_id,
+    };
+    channel_pair(deps.storage).save(&pair)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "handle_set_channel_pair")
+        .add_attribute("reflect_channel_id", pair.reflect_channel_id)
+        .add_attribute("transfer_channel_id", pair.transfer_channel_id))
+}
+
+pub fn handle_send_funds_default(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pair = channel_pair_read(deps.storage)
+        .may_load()?
+        .ok_or(ContractError::NoChannelPair {})?;
+    handle_send_funds(
+        deps,
+        env,
+        info,
+        pair.reflect_channel_id,
+        pair.transfer_channel_id,
+    )
+}
+
+pub fn handle_withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    to_address: String,
+    amount: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    // auth check
+    let cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+    let amount = amount.normalize();
+    if amount.is_empty() {
+        return Err(ContractError::EmptyWithdraw {});
+    }
+    let to_address = deps.api.addr_validate(&to_address)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: to_address.to_string(),
+            amount,
+        })
+        .add_attribute("action", "handle_withdraw")
+        .add_attribute("to_address", to_address))
+}
+
+#[cfg(feature = "test-utils")]
+pub fn handle_test_inject_ack(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel_id: String,
+    packet: PacketMsg,
+    ack: Binary,
+) -> Result<Response, ContractError> {
+    // auth check
+    let cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+
+    let res = acknowledge_packet(deps, env, channel_id, packet, &ack)?;
+    Ok(Response::new()
+        .add_submessages(res.messages)
+        .add_attributes(res.attributes)
+        .add_events(res.events))
+}
+
+pub fn handle_propose_drain(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // auth check
+    let cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+
+    // a new proposal replaces any pending one
+    let nonce = drain_nonce(deps.storage).may_load()?.unwrap_or_default() + 1;
+    drain_nonce(deps.storage).save(&nonce)?;
+    let drain = PendingDrain {
+        nonce,
+        unlock_height: env.block.height + DRAIN_DELAY_BLOCKS,
+    };
+    pending_drain(deps.storage).save(&drain)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "handle_propose_drain")
+        .add_attribute("nonce", nonce.to_string())
+        .add_attribute("unlock_height", drain.unlock_height.to_string()))
+}
+
+pub fn handle_execute_drain(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nonce: u64,
+) -> Result<Response, ContractError> {
+    // auth check
+    let cfg = config(deps.storage).load()?;
+    ensure_admin(&cfg, &info.sender)?;
+
+    let drain = match pending_drain_read(deps.storage).may_load()? {
+        Some(drain) => drain,
+        None => return Err(ContractError::NoDrainProposed {}),
+    };
+    if drain.nonce != nonce {
+        return Err(ContractError::DrainNonceMismatch {});
+    }
+    if env.block.height < drain.unlock_height {
+        return Err(ContractError::DrainLocked {
+            unlock_height: drain.unlock_height,
+        });
+    }
+    pending_drain(deps.storage).remove();
+
+    let balance = deps.querier.query_all_balances(env.contract.address)?;
+    let mut res = Response::new().add_attribute("action", "handle_execute_drain");
+    if !balance.is_empty() {
+        res = res.add_message(BankMsg::Send {
+            to_address: info.sender.into(),
+            amount: balance,
+        });
+    }
     Ok(res)
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<QueryResponse> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<QueryResponse> {
     match msg {
         QueryMsg::Admin {} => to_binary(&query_admin(deps)?),
-        QueryMsg::Account { channel_id } => to_binary(&query_account(deps, channel_id)?),
-        QueryMsg::ListAccounts {} => to_binary(&query_list_accounts(deps)?),
+        QueryMsg::Account {
+            channel_id,
+            max_age,
+        } => to_binary(&query_account(deps, env, channel_id, max_age)?),
+        QueryMsg::ListAccounts {
+            start_after,
+            limit,
+            order,
+        } => to_binary(&query_list_accounts(deps, start_after, limit, order)?),
+        QueryMsg::ListAccountsSorted { start_after, limit } => {
+            to_binary(&query_list_accounts_sorted(deps, start_after, limit)?)
+        }
+        QueryMsg::ListAccountSummaries { start_after, limit } => {
+            to_binary(&query_list_account_summaries(deps, start_after, limit)?)
+        }
+        QueryMsg::AccountByRemote { remote_addr } => {
+            to_binary(&query_account_by_remote(deps, remote_addr)?)
+        }
+        QueryMsg::PendingAccounts { start_after, limit } => {
+            to_binary(&query_pending_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::PreviewDispatch { channel_id, msgs } => {
+            to_binary(&query_preview_dispatch(deps, env, channel_id, msgs)?)
+        }
+        QueryMsg::TotalBalances { start_after, limit } => {
+            to_binary(&query_total_balances(deps, start_after, limit)?)
+        }
+        #[cfg(feature = "debug")]
+        QueryMsg::Raw { key } => to_binary(&query_raw(deps, key)),
     }
 }
 
-fn query_account(deps: Deps, channel_id: String) -> StdResult<AccountResponse> {
+#[cfg(feature = "debug")]
+fn query_raw(deps: Deps, key: Binary) -> Option<Binary> {
+    deps.storage.get(&key).map(Binary)
+}
+
+fn query_account(
+    deps: Deps,
+    env: Env,
+    channel_id: String,
+    max_age: Option<u64>,
+) -> StdResult<AccountResponse> {
     let account = accounts_read(deps.storage).load(channel_id.as_bytes())?;
-    Ok(account.into())
+    let stale =
+        max_age.map(|max_age| account.last_update_time.plus_seconds(max_age) < env.block.time);
+    Ok(AccountResponse {
+        stale,
+        ..account.into()
+    })
 }
 
-fn query_list_accounts(deps: Deps) -> StdResult<ListAccountsResponse> {
-    let accounts: StdResult<Vec<_>> = accounts_read(deps.storage)
-        .range(None, None, Order::Ascending)
-        .map(|r| {
-            let (k, account) = r?;
-            let channel_id = String::from_utf8(k)?;
-            Ok(AccountInfo::convert(channel_id, account))
+fn query_list_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order: Option<Order>,
+) -> StdResult<ListAccountsResponse> {
+    let order = order.unwrap_or(Order::Ascending);
+    let start_after = start_after.as_ref().map(|channel_id| channel_id.as_bytes());
+    let accounts: Vec<_> = paginate(&accounts_read(deps.storage), start_after, limit, order)?
+        .into_iter()
+        .map(|record| {
+            let (channel_id, account) = decode_account(record)?;
+            Ok(AccountInfo::new(channel_id, account))
         })
-        .collect();
-    Ok(ListAccountsResponse {
-        accounts: accounts?,
+        .collect::<StdResult<_>>()?;
+    // same limit as applied by paginate
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let next = match accounts.last() {
+        Some(last) if accounts.len() == limit => Some(last.channel_id.clone()),
+        _ => None,
+    };
+    Ok(ListAccountsResponse { accounts, next })
+}
+
+fn query_list_accounts_sorted(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListAccountsResponse> {
+    // storage is ordered by bytes, so we paginate over the index ordered by channel number
+    let start_after = start_after.map(|channel_id| sorted_index_key(&channel_id));
+    let accounts: Vec<_> = paginate(
+        &sorted_index_read(deps.storage),
+        start_after.as_deref(),
+        limit,
+        Order::Ascending,
+    )?
+    .into_iter()
+    .map(|(_, channel_id)| {
+        let account = accounts_read(deps.storage).load(channel_id.as_bytes())?;
+        Ok(AccountInfo::new(channel_id, account))
+    })
+    .collect::<StdResult<_>>()?;
+    // same limit as applied by paginate
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let next = match accounts.last() {
+        Some(last) if accounts.len() == limit => Some(last.channel_id.clone()),
+        _ => None,
+    };
+    Ok(ListAccountsResponse { accounts, next })
+}
+
+fn query_list_account_summaries(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListAccountSummariesResponse> {
+    let start_after = start_after.as_ref().map(|channel_id| channel_id.as_bytes());
+    let accounts: Vec<_> = paginate(
+        &accounts_read(deps.storage),
+        start_after,
+        limit,
+        Order::Ascending,
+    )?
+    .into_iter()
+    .map(|record| decode_account(record).map(AccountSummary::from))
+    .collect::<StdResult<_>>()?;
+    // same limit as applied by paginate
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let next = match accounts.last() {
+        Some(last) if accounts.len() == limit => Some(last.channel_id.clone()),
+        _ => None,
+    };
+    Ok(ListAccountSummariesResponse { accounts, next })
+}
+
+/// All accounts keyed by channel id
+pub fn accounts_map(deps: Deps) -> StdResult<BTreeMap<String, AccountData>> {
+    accounts_read(deps.storage)
+        .range(None, None, Order::Ascending)
+        .map(|item| decode_account(item?))
+        .collect()
+}
+
+/// Decodes the channel id key of an account record
+fn decode_account((key, account): Record<AccountData>) -> StdResult<(String, AccountData)> {
+    Ok((String::from_utf8(key)?, account))
+}
+
+fn query_account_by_remote(deps: Deps, remote_addr: String) -> StdResult<AccountByRemoteResponse> {
+    let channel_ids = remote_index_read(deps.storage)
+        .may_load(remote_addr.as_bytes())?
+        .unwrap_or_default();
+    Ok(AccountByRemoteResponse { channel_ids })
+}
+
+fn query_preview_dispatch(
+    deps: Deps,
+    env: Env,
+    channel_id: String,
+    msgs: Vec<CosmosMsg>,
+) -> StdResult<PreviewDispatchResponse> {
+    let (packet, timeout) =
+        dispatch_packet(deps.storage, &env, &channel_id, msgs).map_err(|err| match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        })?;
+    Ok(PreviewDispatchResponse {
+        packet,
+        timeout_timestamp: timeout.timestamp(),
+        timeout_block: timeout.block(),
     })
 }
 
+fn query_pending_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PendingAccountsResponse> {
+    let start_after = start_after.as_ref().map(|channel_id| channel_id.as_bytes());
+    let accounts = paginate(
+        &accounts_read(deps.storage),
+        start_after,
+        limit,
+        Order::Ascending,
+    )?;
+    // same limit as applied by paginate, the next page starts after the last account
+    // looked at, no matter if it is pending
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let next = match accounts.last() {
+        Some(last) if accounts.len() == limit => Some(decode_account(last.clone())?.0),
+        _ => None,
+    };
+
+    let channel_ids = accounts
+        .into_iter()
+        .filter(|(_, account)| account.remote_addr.is_none())
+        .map(|record| decode_account(record).map(|(channel_id, _)| channel_id))
+        .collect::<StdResult<_>>()?;
+    Ok(PendingAccountsResponse { channel_ids, next })
+}
+
+fn query_total_balances(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TotalBalancesResponse> {
+    let start_after = start_after.as_ref().map(|channel_id| channel_id.as_bytes());
+    let accounts = paginate(
+        &accounts_read(deps.storage),
+        start_after,
+        limit,
+        Order::Ascending,
+    )?;
+    // same limit as applied by paginate
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let next = match accounts.last() {
+        Some(last) if accounts.len() == limit => Some(decode_account(last.clone())?.0),
+        _ => None,
+    };
+
+    let mut balances = vec![];
+    for (_, account) in accounts {
+        if account.remote_balance.is_empty() {
+            continue;
+        }
+        balances = add_coins(balances, account.remote_balance)?;
+    }
+    balances.sort_by(|a, b| a.denom.cmp(&b.denom));
+    Ok(TotalBalancesResponse { balances, next })
+}
+
 fn query_admin(deps: Deps) -> StdResult<AdminResponse> {
-    let Config { admin } = config_read(deps.storage).load()?;
+    let Config { admin, .. } = config_read(deps.storage).load()?;
     Ok(AdminResponse {
-        admin: admin.into(),
+        admin: admin.map(Into::into),
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use crate::state::{channel_sort_key, sorted_index};
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
+    };
+    use cosmwasm_std::{attr, coin, coins, from_binary, Empty, SubMsg, Timestamp};
 
     const CREATOR: &str = "creator";
 
+    fn list_channel_ids(
+        deps: Deps,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+        order: Option<Order>,
+    ) -> Vec<String> {
+        let msg = QueryMsg::ListAccounts {
+            start_after: start_after.map(String::from),
+            limit,
+            order,
+        };
+        let res = query(deps, mock_env(), msg).unwrap();
+        let res: ListAccountsResponse = from_binary(&res).unwrap();
+        res.accounts.into_iter().map(|a| a.channel_id).collect()
+    }
+
+    #[test]
+    fn ensure_admin_works() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        let mut cfg = config_read(deps.as_ref().storage).load().unwrap();
+
+        ensure_admin(&cfg, &Addr::unchecked(CREATOR)).unwrap();
+        let err = ensure_admin(&cfg, &Addr::unchecked("someone")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // nobody is the admin after renouncing
+        cfg.admin = None;
+        let err = ensure_admin(&cfg, &Addr::unchecked(CREATOR)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
     #[test]
     fn instantiate_works() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg::default();
         let info = mock_info(CREATOR, &[]);
         let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
 
         let admin = query_admin(deps.as_ref()).unwrap();
-        assert_eq!(CREATOR, admin.admin.as_str());
+        assert_eq!(admin.admin.as_deref(), Some(CREATOR));
+    }
+
+    #[test]
+    fn total_balances_aggregates_accounts() {
+        let mut deps = mock_dependencies();
+
+        let total = |deps: Deps, start_after: Option<String>, limit: Option<u32>| {
+            let msg = QueryMsg::TotalBalances { start_after, limit };
+            let res: TotalBalancesResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res
+        };
+
+        // empty without accounts
+        let res = total(deps.as_ref(), None, None);
+        assert!(res.balances.is_empty());
+        assert_eq!(res.next, None);
+
+        let balances = [
+            vec![coin(100, "uatom"), coin(5, "ustake")],
+            vec![coin(7, "ustake"), coin(12, "ucosm")],
+            vec![coin(1, "uatom"), coin(3, "uosmo")],
+        ];
+        for (i, remote_balance) in balances.into_iter().enumerate() {
+            let data = AccountData {
+                remote_balance,
+                ..AccountData::default()
+            };
+            accounts(deps.as_mut().storage)
+                .save(format!("channel-{}", i).as_bytes(), &data)
+                .unwrap();
+        }
+        // accounts without balance data are skipped
+        accounts(deps.as_mut().storage)
+            .save(b"channel-9", &AccountData::default())
+            .unwrap();
+
+        let expected = vec![
+            coin(101, "uatom"),
+            coin(12, "ucosm"),
+            coin(3, "uosmo"),
+            coin(12, "ustake"),
+        ];
+        let res = total(deps.as_ref(), None, None);
+        assert_eq!(res.balances, expected);
+        assert_eq!(res.next, None);
+
+        // paginated, the pages add up to the same total
+        let first = total(deps.as_ref(), None, Some(2));
+        assert_eq!(
+            first.balances,
+            vec![coin(100, "uatom"), coin(12, "ucosm"), coin(12, "ustake")]
+        );
+        assert_eq!(first.next.as_deref(), Some("channel-1"));
+        let second = total(deps.as_ref(), first.next, Some(2));
+        assert_eq!(second.balances, vec![coin(1, "uatom"), coin(3, "uosmo")]);
+        assert_eq!(second.next.as_deref(), Some("channel-9"));
+        let last = total(deps.as_ref(), second.next, Some(2));
+        assert!(last.balances.is_empty());
+        assert_eq!(last.next, None);
+        let mut sum = add_coins(first.balances, second.balances).unwrap();
+        sum.sort_by(|a, b| a.denom.cmp(&b.denom));
+        assert_eq!(sum, expected);
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn query_raw_works() {
+        use crate::state::KEY_CONFIG;
+        use cosmwasm_std::from_slice;
+        use cosmwasm_storage::to_length_prefixed;
+
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        // singletons are stored under the length prefixed key
+        let msg = QueryMsg::Raw {
+            key: to_length_prefixed(KEY_CONFIG).into(),
+        };
+        let res: Option<Binary> =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let stored: Config = from_slice(&res.unwrap()).unwrap();
+        assert_eq!(stored, config_read(deps.as_ref().storage).load().unwrap());
+
+        let msg = QueryMsg::Raw {
+            key: b"missing".into(),
+        };
+        let res: Option<Binary> =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn send_msgs_multi_works() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        for channel_id in ["channel-1", "channel-2", "channel-3"] {
+            accounts(deps.as_mut().storage)
+                .save(channel_id.as_bytes(), &AccountData::default())
+                .unwrap();
+        }
+        let msgs: Vec<CosmosMsg> = vec![BankMsg::Send {
+            to_address: "my-friend".into(),
+            amount: coins(123, "ucosm"),
+        }
+        .into()];
+
+        let msg = ExecuteMsg::SendMsgsMulti {
+            channel_ids: vec!["channel-1".into(), "channel-3".into(), "channel-2".into()],
+            msgs: msgs.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        let timeout = packet_timeout(&mock_env(), &config_read(&deps.storage).load().unwrap());
+        let data = to_binary(&PacketMsg::Dispatch { msgs: msgs.clone() }).unwrap();
+        let expected: Vec<_> = ["channel-1", "channel-3", "channel-2"]
+            .into_iter()
+            .map(|channel_id| {
+                SubMsg::new(IbcMsg::SendPacket {
+                    channel_id: channel_id.into(),
+                    data: data.clone(),
+                    timeout: timeout.clone(),
+                })
+            })
+            .collect();
+        assert_eq!(res.messages, expected);
+        assert_eq!(res.events.len(), 3);
+        assert_eq!(res.attributes, vec![("action", "handle_send_msgs_multi")]);
+
+        // one unknown channel fails the whole call
+        let msg = ExecuteMsg::SendMsgsMulti {
+            channel_ids: vec!["channel-1".into(), "channel-9".into(), "channel-2".into()],
+            msgs: msgs.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ChannelNotFound {
+                channel_id: "channel-9".into()
+            }
+        );
+
+        // only the admin
+        let msg = ExecuteMsg::SendMsgsMulti {
+            channel_ids: vec!["channel-1".into()],
+            msgs: msgs.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // channel count is bounded
+        for count in [0, MAX_CHANNELS_PER_BATCH + 1] {
+            let msg = ExecuteMsg::SendMsgsMulti {
+                channel_ids: vec!["channel-1".into(); count],
+                msgs: msgs.clone(),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidChannelCount {
+                    count,
+                    max: MAX_CHANNELS_PER_BATCH
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn validate_msgs_works() {
+        let send = |to_address: &str, amount: Vec<Coin>| -> CosmosMsg {
+            BankMsg::Send {
+                to_address: to_address.into(),
+                amount,
+            }
+            .into()
+        };
+        let execute = |contract_addr: &str| -> CosmosMsg {
+            WasmMsg::Execute {
+                contract_addr: contract_addr.into(),
+                msg: b"{}".into(),
+                funds: vec![],
+            }
+            .into()
+        };
+
+        // valid lists pass
+        validate_msgs(&[]).unwrap();
+        validate_msgs(&[
+            send("my-friend", coins(12, "ucosm")),
+            execute("some-contract"),
+            BankMsg::Burn {
+                amount: coins(1, "ucosm"),
+            }
+            .into(),
+        ])
+        .unwrap();
+
+        // empty recipient
+        let err = validate_msgs(&[
+            send("my-friend", coins(12, "ucosm")),
+            send("", coins(12, "ucosm")),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Message 1: bank recipient must not be empty")
+        );
+
+        // zero amounts
+        let err = validate_msgs(&[send("my-friend", vec![])]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Message 0: bank send amount must not be zero")
+        );
+        let err = validate_msgs(&[send(
+            "my-friend",
+            vec![coin(12, "ucosm"), coin(0, "ustake")],
+        )])
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Message 0: bank send amount must not be zero")
+        );
+
+        // empty contract address
+        let err = validate_msgs(&[execute("")]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Message 0: contract address must not be empty")
+        );
+    }
+
+    #[test]
+    fn send_msgs_rejects_malformed_msgs() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        accounts(deps.as_mut().storage)
+            .save(b"channel-1", &AccountData::default())
+            .unwrap();
+
+        let msg = ExecuteMsg::SendMsgs {
+            channel_id: "channel-1".into(),
+            msgs: vec![BankMsg::Send {
+                to_address: "".into(),
+                amount: coins(12, "ucosm"),
+            }
+            .into()],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Std(StdError::generic_err(
+                "Message 0: bank recipient must not be empty"
+            ))
+        );
+    }
+
+    #[test]
+    fn handlers_emit_typed_events() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "ucosm"));
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        let account = AccountData {
+            remote_addr: Some("account-789".into()),
+            ..AccountData::default()
+        };
+        accounts(deps.as_mut().storage)
+            .save(b"channel-1", &account)
+            .unwrap();
+
+        let msg = ExecuteMsg::SendMsgs {
+            channel_id: "channel-1".into(),
+            msgs: vec![],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        // the action attribute is kept for backwards compatibility
+        assert_eq!(res.attributes, vec![("action", "handle_send_msgs")]);
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "ibc_dispatch");
+        assert_eq!(
+            res.events[0].attributes,
+            vec![("channel_id", "channel-1"), ("msgs", "0")]
+        );
+
+        let msg = ExecuteMsg::CheckRemoteBalance {
+            channel_id: "channel-1".into(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![("action", "handle_check_remote_balance")]
+        );
+        assert_eq!(
+            res.events,
+            vec![Event::new("ibc_check_balance").add_attribute("channel_id", "channel-1")]
+        );
+
+        let msg = ExecuteMsg::SendFunds {
+            reflect_channel_id: "channel-1".into(),
+            transfer_channel_id: "transfer-1".into(),
+        };
+        let info = mock_info("anyone", &coins(12, "ucosm"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes, vec![("action", "handle_send_funds")]);
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "ibc_transfer");
+        assert_eq!(
+            res.events[0].attributes,
+            vec![
+                ("reflect_channel_id", "channel-1"),
+                ("transfer_channel_id", "transfer-1"),
+                ("to_address", "account-789"),
+                ("amount", "12ucosm"),
+            ]
+        );
+    }
+
+    #[test]
+    fn accounts_map_works() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        assert!(accounts_map(deps.as_ref()).unwrap().is_empty());
+
+        let account = |addr: &str, amount: u128| AccountData {
+            last_update_time: Timestamp::from_seconds(amount as u64),
+            remote_addr: Some(addr.to_string()),
+            remote_balance: coins(amount, "ucosm"),
+        };
+        for (channel_id, data) in [
+            ("channel-7", account("account-7", 7)),
+            ("channel-12", account("account-12", 12)),
+            ("channel-3", AccountData::default()),
+        ] {
+            accounts(deps.as_mut().storage)
+                .save(channel_id.as_bytes(), &data)
+                .unwrap();
+        }
+
+        let map = accounts_map(deps.as_ref()).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["channel-7"], account("account-7", 7));
+        assert_eq!(map["channel-12"], account("account-12", 12));
+        assert_eq!(map["channel-3"], AccountData::default());
+        assert_eq!(map.get("channel-4"), None);
+        // sorted by channel id
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            vec!["channel-12", "channel-3", "channel-7"]
+        );
+    }
+
+    #[test]
+    fn query_account_reports_staleness() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let env = mock_env();
+        let data = AccountData {
+            last_update_time: env.block.time.minus_seconds(100),
+            remote_addr: Some("account-789".to_string()),
+            remote_balance: coins(12, "ucosm"),
+        };
+        accounts(deps.as_mut().storage)
+            .save(b"channel-7", &data)
+            .unwrap();
+        let query_stale = |max_age: Option<u64>| {
+            let msg = QueryMsg::Account {
+                channel_id: "channel-7".to_string(),
+                max_age,
+            };
+            let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+            let account: AccountResponse = from_binary(&res).unwrap();
+            assert_eq!(account.last_update_time, data.last_update_time);
+            account.stale
+        };
+
+        // not computed without max_age
+        assert_eq!(query_stale(None), None);
+        // fresh
+        assert_eq!(query_stale(Some(3600)), Some(false));
+        assert_eq!(query_stale(Some(100)), Some(false));
+        // stale
+        assert_eq!(query_stale(Some(99)), Some(true));
+        assert_eq!(query_stale(Some(0)), Some(true));
+    }
+
+    #[test]
+    fn pending_accounts_lists_unresolved_channels() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        let pending = |deps: Deps, start_after: Option<&str>, limit: Option<u32>| {
+            let msg = QueryMsg::PendingAccounts {
+                start_after: start_after.map(String::from),
+                limit,
+            };
+            let res: PendingAccountsResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            (res.channel_ids, res.next)
+        };
+        assert_eq!(pending(deps.as_ref(), None, None), (vec![], None));
+
+        let resolved = AccountData {
+            remote_addr: Some("account-789".to_string()),
+            ..AccountData::default()
+        };
+        for (channel_id, data) in [
+            ("channel-4", AccountData::default()),
+            ("channel-1", resolved.clone()),
+            ("channel-2", AccountData::default()),
+            ("channel-3", resolved),
+        ] {
+            accounts(deps.as_mut().storage)
+                .save(channel_id.as_bytes(), &data)
+                .unwrap();
+        }
+        assert_eq!(
+            pending(deps.as_ref(), None, None),
+            (vec!["channel-2".to_string(), "channel-4".to_string()], None)
+        );
+
+        // paginated, where a page can be empty
+        let (ids, next) = pending(deps.as_ref(), None, Some(1));
+        assert!(ids.is_empty());
+        assert_eq!(next.as_deref(), Some("channel-1"));
+        let (ids, next) = pending(deps.as_ref(), next.as_deref(), Some(2));
+        assert_eq!(ids, vec!["channel-2"]);
+        assert_eq!(next.as_deref(), Some("channel-3"));
+        let (ids, next) = pending(deps.as_ref(), next.as_deref(), Some(2));
+        assert_eq!(ids, vec!["channel-4"]);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn send_msgs_enforces_max_msgs_per_packet() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_msgs_per_packet: Some(3),
+            ..InstantiateMsg::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        accounts(deps.as_mut().storage)
+            .save(b"channel-1", &AccountData::default())
+            .unwrap();
+        let send_msgs = |count: usize| ExecuteMsg::SendMsgs {
+            channel_id: "channel-1".into(),
+            msgs: vec![
+                BankMsg::Send {
+                    to_address: "my-friend".into(),
+                    amount: coins(1, "ucosm"),
+                }
+                .into();
+                count
+            ],
+        };
+
+        // at the limit
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            send_msgs(3),
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+
+        // over the limit
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            send_msgs(4),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TooManyMsgs { count: 4, max: 3 });
+        assert_eq!(
+            err.to_string(),
+            "Cannot send 4 messages in one packet, the maximum is 3"
+        );
+
+        // only the admin may change the limit
+        let update = ExecuteMsg::UpdateMaxMsgsPerPacket {
+            max_msgs_per_packet: 4,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            update.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), update).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "handle_update_max_msgs_per_packet"),
+                ("max_msgs_per_packet", "4")
+            ]
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            send_msgs(4),
+        )
+        .unwrap();
+
+        // the default applies if not set
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        let cfg = config_read(deps.as_ref().storage).load().unwrap();
+        assert_eq!(cfg.max_msgs_per_packet, DEFAULT_MAX_MSGS_PER_PACKET);
+    }
+
+    #[test]
+    fn unregistered_channel_gives_friendly_error() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        let not_found = ContractError::ChannelNotFound {
+            channel_id: "channel-404".into(),
+        };
+
+        assert_eq!(
+            load_account_or_err(deps.as_ref().storage, "channel-404").unwrap_err(),
+            not_found
+        );
+
+        let msgs = vec![
+            ExecuteMsg::SendMsgs {
+                channel_id: "channel-404".into(),
+                msgs: vec![],
+            },
+            ExecuteMsg::CheckRemoteBalance {
+                channel_id: "channel-404".into(),
+            },
+        ];
+        for msg in msgs {
+            let info = mock_info(CREATOR, &[coin(12, "ucosm")]);
+            let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+            assert_eq!(err, not_found);
+            assert_eq!(err.to_string(), "No account for channel channel-404");
+        }
+
+        // a registered channel is loaded as is
+        accounts(deps.as_mut().storage)
+            .save(b"channel-1", &AccountData::default())
+            .unwrap();
+        assert_eq!(
+            load_account_or_err(deps.as_ref().storage, "channel-1").unwrap(),
+            AccountData::default()
+        );
+    }
+
+    #[test]
+    fn execute_returns_typed_errors() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        accounts(deps.as_mut().storage)
+            .save(b"channel-1", &AccountData::default())
+            .unwrap();
+
+        // only the admin may update the admin or send messages
+        let msg = ExecuteMsg::UpdateAdmin {
+            admin: "someone".into(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let msg = ExecuteMsg::SendMsgs {
+            channel_id: "channel-1".into(),
+            msgs: vec![],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // unknown channel
+        let msg = ExecuteMsg::CheckRemoteBalance {
+            channel_id: "channel-2".into(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ChannelNotFound {
+                channel_id: "channel-2".into()
+            }
+        );
+
+        // reflect channel not registered
+        let msg = ExecuteMsg::SendFunds {
+            reflect_channel_id: "channel-2".into(),
+            transfer_channel_id: "transfer-1".into(),
+        };
+        let info = mock_info(CREATOR, &[coin(12, "ucosm")]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReflectChannelNotRegistered {
+                channel_id: "channel-2".into()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Reflect channel channel-2 is not registered, it was never connected or is closed"
+        );
+
+        // remote address not yet known
+        let msg = ExecuteMsg::SendFunds {
+            reflect_channel_id: "channel-1".into(),
+            transfer_channel_id: "transfer-1".into(),
+        };
+        let info = mock_info(CREATOR, &[coin(12, "ucosm")]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NoRemoteAddr {
+                channel_id: "channel-1".into()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "The remote address for channel channel-1 is not known yet, wait for the WhoAmI acknowledgement"
+        );
+    }
+
+    #[test]
+    fn list_accounts_returns_next() {
+        let mut deps = mock_dependencies();
+        for i in 1..=5 {
+            accounts(deps.as_mut().storage)
+                .save(format!("channel-{}", i).as_bytes(), &AccountData::default())
+                .unwrap();
+        }
+        let list = |deps: Deps, start_after: Option<String>| -> ListAccountsResponse {
+            let msg = QueryMsg::ListAccounts {
+                start_after,
+                limit: Some(2),
+                order: None,
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // full pages point to their last channel id
+        let page = list(deps.as_ref(), None);
+        assert_eq!(page.accounts.len(), 2);
+        assert_eq!(page.next, Some("channel-2".to_string()));
+        let page = list(deps.as_ref(), page.next);
+        assert_eq!(page.accounts.len(), 2);
+        assert_eq!(page.next, Some("channel-4".to_string()));
+
+        // the last page is not full
+        let page = list(deps.as_ref(), page.next);
+        assert_eq!(page.accounts.len(), 1);
+        assert_eq!(page.accounts[0].channel_id, "channel-5");
+        assert_eq!(page.next, None);
+
+        // default limit is not reached
+        let msg = QueryMsg::ListAccounts {
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        let page: ListAccountsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(page.accounts.len(), 5);
+        assert_eq!(page.next, None);
+    }
+
+    #[test]
+    fn list_account_summaries_works() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        let resolved = AccountData {
+            last_update_time: Timestamp::from_seconds(7),
+            remote_addr: Some("account-789".to_string()),
+            remote_balance: vec![coin(12, "ucosm"), coin(3, "ustake")],
+        };
+        for (channel_id, data) in [
+            ("channel-1", resolved),
+            ("channel-2", AccountData::default()),
+            ("channel-3", AccountData::default()),
+        ] {
+            accounts(deps.as_mut().storage)
+                .save(channel_id.as_bytes(), &data)
+                .unwrap();
+        }
+
+        let msg = QueryMsg::ListAccountSummaries {
+            start_after: None,
+            limit: Some(2),
+        };
+        let res: ListAccountSummariesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.accounts,
+            vec![
+                AccountSummary {
+                    channel_id: "channel-1".to_string(),
+                    has_remote_addr: true,
+                    denom_count: 2,
+                    last_update_time: Timestamp::from_seconds(7),
+                },
+                AccountSummary {
+                    channel_id: "channel-2".to_string(),
+                    has_remote_addr: false,
+                    denom_count: 0,
+                    last_update_time: Timestamp::from_nanos(0),
+                },
+            ]
+        );
+        assert_eq!(res.next.as_deref(), Some("channel-2"));
+
+        let msg = QueryMsg::ListAccountSummaries {
+            start_after: res.next,
+            limit: Some(2),
+        };
+        let res: ListAccountSummariesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.accounts.len(), 1);
+        assert_eq!(res.accounts[0].channel_id, "channel-3");
+        assert_eq!(res.next, None);
+    }
+
+    #[test]
+    fn list_accounts_orders_by_bytes() {
+        let mut deps = mock_dependencies();
+        for channel_id in ["channel-2", "channel-10", "channel-1", "channel-100"] {
+            accounts(deps.as_mut().storage)
+                .save(channel_id.as_bytes(), &AccountData::default())
+                .unwrap();
+        }
+
+        // multi-digit ids are not ordered by number
+        let ids = list_channel_ids(deps.as_ref(), None, None, None);
+        assert_eq!(
+            ids,
+            vec!["channel-1", "channel-10", "channel-100", "channel-2"]
+        );
+
+        // pages follow the same order, so nothing is skipped
+        let ids = list_channel_ids(deps.as_ref(), Some("channel-10"), Some(2), None);
+        assert_eq!(ids, vec!["channel-100", "channel-2"]);
+    }
+
+    #[test]
+    fn list_accounts_sorted_orders_by_channel_number() {
+        let mut deps = mock_dependencies();
+        for channel_id in [
+            "channel-2",
+            "channel-10",
+            "channel-1",
+            "channel-100",
+            "channel-02",
+            "other",
+        ] {
+            accounts(deps.as_mut().storage)
+                .save(channel_id.as_bytes(), &AccountData::default())
+                .unwrap();
+            sorted_index(deps.as_mut().storage)
+                .save(&sorted_index_key(channel_id), &channel_id.to_string())
+                .unwrap();
+        }
+        let list = |deps: Deps, start_after: Option<&str>, limit: Option<u32>| {
+            let msg = QueryMsg::ListAccountsSorted {
+                start_after: start_after.map(String::from),
+                limit,
+            };
+            let res: ListAccountsResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            let ids: Vec<_> = res.accounts.into_iter().map(|a| a.channel_id).collect();
+            (ids, res.next)
+        };
+
+        let (ids, next) = list(deps.as_ref(), None, None);
+        assert_eq!(
+            ids,
+            vec![
+                "channel-1",
+                "channel-02",
+                "channel-2",
+                "channel-10",
+                "channel-100",
+                "other"
+            ]
+        );
+        assert_eq!(next, None);
+
+        // paginated, where ids with the same channel number do not get lost between pages
+        let (ids, next) = list(deps.as_ref(), None, Some(2));
+        assert_eq!(ids, vec!["channel-1", "channel-02"]);
+        assert_eq!(next.as_deref(), Some("channel-02"));
+        let (ids, next) = list(deps.as_ref(), next.as_deref(), Some(2));
+        assert_eq!(ids, vec!["channel-2", "channel-10"]);
+        let (ids, next) = list(deps.as_ref(), next.as_deref(), Some(2));
+        assert_eq!(ids, vec!["channel-100", "other"]);
+        let (ids, next) = list(deps.as_ref(), next.as_deref(), Some(2));
+        assert!(ids.is_empty());
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn sorted_index_key_works() {
+        // ids sharing a sort key get different index keys
+        assert_ne!(
+            sorted_index_key("channel-2"),
+            sorted_index_key("channel-02")
+        );
+        // a sort key that is a prefix of another one is ordered first
+        assert!(sorted_index_key("channel") < sorted_index_key("channel-1"));
+        assert!(sorted_index_key("channel-2") < sorted_index_key("channel-10"));
+    }
+
+    #[test]
+    fn channel_sort_key_works() {
+        assert_eq!(
+            channel_sort_key("channel-2"),
+            "channel-00000000000000000002"
+        );
+        assert_eq!(
+            channel_sort_key("channel-18446744073709551615"),
+            "channel-18446744073709551615"
+        );
+        assert!(channel_sort_key("channel-2") < channel_sort_key("channel-10"));
+        // ids without numeric suffix are kept
+        assert_eq!(channel_sort_key("channel"), "channel");
+        assert_eq!(channel_sort_key("channel-"), "channel-");
+        assert_eq!(channel_sort_key("channel-1a"), "channel-1a");
+        assert_eq!(channel_sort_key("channel-+1"), "channel-+1");
+    }
+
+    #[test]
+    fn list_accounts_respects_order() {
+        let mut deps = mock_dependencies();
+        for (i, channel_id) in ["channel-2", "channel-1", "channel-3"].iter().enumerate() {
+            let data = AccountData {
+                last_update_time: Timestamp::from_seconds(i as u64),
+                ..AccountData::default()
+            };
+            accounts(deps.as_mut().storage)
+                .save(channel_id.as_bytes(), &data)
+                .unwrap();
+        }
+
+        let ascending = vec!["channel-1", "channel-2", "channel-3"];
+        let descending = vec!["channel-3", "channel-2", "channel-1"];
+
+        // default is ascending
+        assert_eq!(list_channel_ids(deps.as_ref(), None, None, None), ascending);
+        assert_eq!(
+            list_channel_ids(deps.as_ref(), None, None, Some(Order::Ascending)),
+            ascending
+        );
+        assert_eq!(
+            list_channel_ids(deps.as_ref(), None, None, Some(Order::Descending)),
+            descending
+        );
+
+        // start_after is an exclusive bound in both directions
+        assert_eq!(
+            list_channel_ids(deps.as_ref(), Some("channel-2"), None, None),
+            vec!["channel-3"]
+        );
+        assert_eq!(
+            list_channel_ids(
+                deps.as_ref(),
+                Some("channel-2"),
+                None,
+                Some(Order::Descending)
+            ),
+            vec!["channel-1"]
+        );
+
+        // limit
+        assert_eq!(
+            list_channel_ids(deps.as_ref(), None, Some(2), Some(Order::Descending)),
+            vec!["channel-3", "channel-2"]
+        );
+    }
+
+    #[test]
+    fn drain_works() {
+        let balance = vec![coin(123, "ucosm"), coin(456, "ustake")];
+        let mut deps = mock_dependencies_with_balance(&balance);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            InstantiateMsg::default(),
+        )
+        .unwrap();
+
+        let info = mock_info(CREATOR, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ProposeDrain {}).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(res.attributes[1], ("nonce", "1"));
+
+        let mut env = mock_env();
+        env.block.height += DRAIN_DELAY_BLOCKS;
+        let info = mock_info(CREATOR, &[]);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ExecuteDrain { nonce: 1 },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: CREATOR.into(),
+                amount: balance,
+            })]
+        );
+
+        // cannot be executed twice
+        let mut env = mock_env();
+        env.block.height += DRAIN_DELAY_BLOCKS;
+        let info = mock_info(CREATOR, &[]);
+        let err = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ExecuteDrain { nonce: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoDrainProposed {});
+    }
+
+    #[test]
+    fn drain_requires_admin() {
+        let mut deps = mock_dependencies_with_balance(&[coin(123, "ucosm")]);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            InstantiateMsg::default(),
+        )
+        .unwrap();
+
+        let info = mock_info("intruder", &[]);
+        let err =
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ProposeDrain {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn drain_rejects_wrong_nonce() {
+        let mut deps = mock_dependencies_with_balance(&[coin(123, "ucosm")]);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            InstantiateMsg::default(),
+        )
+        .unwrap();
+
+        // the second proposal invalidates the first nonce
+        let info = mock_info(CREATOR, &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ProposeDrain {},
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ProposeDrain {},
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1], ("nonce", "2"));
+
+        let mut env = mock_env();
+        env.block.height += DRAIN_DELAY_BLOCKS;
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::ExecuteDrain { nonce: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::DrainNonceMismatch {});
+
+        let res = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ExecuteDrain { nonce: 2 },
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+    }
+
+    #[test]
+    fn update_admin_cancels_pending_drain() {
+        let mut deps = mock_dependencies_with_balance(&[coin(123, "ucosm")]);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            InstantiateMsg::default(),
+        )
+        .unwrap();
+
+        let info = mock_info(CREATOR, &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ProposeDrain {},
+        )
+        .unwrap();
+        let msg = ExecuteMsg::UpdateAdmin {
+            admin: "new_admin".into(),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // the nonce is predictable, so the new admin must not be able to use the old proposal
+        let mut env = mock_env();
+        env.block.height += DRAIN_DELAY_BLOCKS;
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("new_admin", &[]),
+            ExecuteMsg::ExecuteDrain { nonce: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoDrainProposed {});
+    }
+
+    #[test]
+    fn drain_rejects_execution_before_unlock() {
+        let mut deps = mock_dependencies_with_balance(&[coin(123, "ucosm")]);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            InstantiateMsg::default(),
+        )
+        .unwrap();
+
+        let info = mock_info(CREATOR, &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ProposeDrain {},
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.height += DRAIN_DELAY_BLOCKS - 1;
+        let err = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ExecuteDrain { nonce: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DrainLocked {
+                unlock_height: mock_env().block.height + DRAIN_DELAY_BLOCKS
+            }
+        );
+    }
+
+    #[test]
+    fn renounce_admin_disables_admin_messages() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        accounts(deps.as_mut().storage)
+            .save(b"channel-1", &AccountData::default())
+            .unwrap();
+
+        // only the admin may renounce
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            ExecuteMsg::RenounceAdmin {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            ExecuteMsg::RenounceAdmin {},
+        )
+        .unwrap();
+        assert_eq!(res.attributes, vec![("action", "handle_renounce_admin")]);
+        assert_eq!(query_admin(deps.as_ref()).unwrap().admin, None);
+
+        // now nobody is admin anymore, including the former one
+        for sender in [CREATOR, "someone"] {
+            let msg = ExecuteMsg::UpdateAdmin {
+                admin: sender.into(),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let msg = ExecuteMsg::SendMsgs {
+                channel_id: "channel-1".into(),
+                msgs: vec![],
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let msg = ExecuteMsg::RenounceAdmin {};
+            let err = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+    }
+
+    #[test]
+    fn attribute_builder_matches_handwritten_attributes() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let info = mock_info(CREATOR, &[]);
+        let msg = ExecuteMsg::UpdateAdmin {
+            admin: "new_admin".into(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let expected = vec![
+            attr("action", "handle_update_admin"),
+            attr("new_admin", "new_admin"),
+        ];
+        assert_eq!(res.attributes, expected);
+
+        // building the same list via an event yields identical attributes
+        let event = Event::new("wasm")
+            .add_attribute("action", "handle_update_admin")
+            .add_attribute("new_admin", "new_admin");
+        assert_eq!(event.attributes, expected);
+
+        // as does bulk adding key/value pairs
+        let res = Response::<Empty>::new().add_attributes(vec![
+            ("action", "handle_update_admin"),
+            ("new_admin", "new_admin"),
+        ]);
+        assert_eq!(res.attributes, expected);
+    }
+
+    #[test]
+    fn send_msgs_builder_matches_field_construction() {
+        let channel_id = "channel-1234";
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        accounts(deps.as_mut().storage)
+            .save(channel_id.as_bytes(), &AccountData::default())
+            .unwrap();
+
+        let msgs: Vec<CosmosMsg> = vec![BankMsg::Send {
+            to_address: "my-friend".into(),
+            amount: vec![coin(123, "ucosm")],
+        }
+        .into()];
+        let info = mock_info(CREATOR, &[]);
+        let res = handle_send_msgs(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            channel_id.into(),
+            msgs.clone(),
+        )
+        .unwrap();
+
+        // Response is non-exhaustive, so the closest to a struct literal is setting
+        // the public fields of a default instance
+        let mut expected = Response::<Empty>::default();
+        expected.messages = vec![SubMsg::new(IbcMsg::SendPacket {
+            channel_id: channel_id.into(),
+            data: to_binary(&PacketMsg::Dispatch { msgs }).unwrap(),
+            timeout: packet_timeout(&mock_env(), &config_read(&deps.storage).load().unwrap()),
+        })];
+        expected.attributes = vec![attr("action", "handle_send_msgs")];
+        expected.events = vec![Event::new("ibc_dispatch")
+            .add_attribute("channel_id", channel_id)
+            .add_attribute("msgs", "1")];
+        expected.data = None;
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn preview_dispatch_matches_send_msgs() {
+        let channel_id = "channel-1234";
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+        accounts(deps.as_mut().storage)
+            .save(channel_id.as_bytes(), &AccountData::default())
+            .unwrap();
+
+        let msgs: Vec<CosmosMsg> = vec![BankMsg::Send {
+            to_address: "my-friend".into(),
+            amount: vec![coin(123, "ucosm")],
+        }
+        .into()];
+        let msg = QueryMsg::PreviewDispatch {
+            channel_id: channel_id.into(),
+            msgs: msgs.clone(),
+        };
+        let preview: PreviewDispatchResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+
+        let info = mock_info(CREATOR, &[]);
+        let res =
+            handle_send_msgs(deps.as_mut(), mock_env(), info, channel_id.into(), msgs).unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Ibc(IbcMsg::SendPacket { data, timeout, .. }) => {
+                assert_eq!(&preview.packet, data);
+                assert_eq!(preview.timeout_timestamp, timeout.timestamp());
+                assert_eq!(preview.timeout_block, timeout.block());
+            }
+            o => panic!("Unexpected message: {:?}", o),
+        }
+
+        // unknown channels are rejected like in SendMsgs
+        let msg = QueryMsg::PreviewDispatch {
+            channel_id: "channel-9".into(),
+            msgs: vec![],
+        };
+        let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(
+                ContractError::ChannelNotFound {
+                    channel_id: "channel-9".into()
+                }
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn withdraw_works() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let msg = ExecuteMsg::Withdraw {
+            to_address: "rescued".into(),
+            amount: vec![coin(123, "ucosm"), coin(0, "ustake")],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "rescued".into(),
+                amount: vec![coin(123, "ucosm")],
+            })]
+        );
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "handle_withdraw"),
+                attr("to_address", "rescued")
+            ]
+        );
+
+        // nothing to withdraw
+        let msg = ExecuteMsg::Withdraw {
+            to_address: "rescued".into(),
+            amount: vec![coin(0, "ucosm")],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyWithdraw {});
+    }
+
+    #[test]
+    fn withdraw_rejects_non_admin() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg::default()).unwrap();
+
+        let msg = ExecuteMsg::Withdraw {
+            to_address: "thief".into(),
+            amount: vec![coin(123, "ucosm")],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("thief", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
     }
 }
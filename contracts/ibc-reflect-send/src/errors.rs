@@ -0,0 +1,45 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    /// this is needed so we can use `bucket.load(...)?` and have it auto-converted to the custom error
+    Std(#[from] StdError),
+    #[error("Unauthorized: only the admin may do this")]
+    Unauthorized {},
+    #[error("No account for channel {channel_id}")]
+    ChannelNotFound { channel_id: String },
+    #[error("Reflect channel {channel_id} is not registered, it was never connected or is closed")]
+    ReflectChannelNotRegistered { channel_id: String },
+    #[error("The remote address for channel {channel_id} is not known yet, wait for the WhoAmI acknowledgement")]
+    NoRemoteAddr { channel_id: String },
+    #[error("You must send the coins you wish to ibc transfer")]
+    NoFunds {},
+    #[error("You can only ibc transfer one coin")]
+    MultipleDenoms {},
+    #[error("cannot transfer zero amount")]
+    ZeroAmount {},
+    #[error("No channel pair set for sending funds")]
+    NoChannelPair {},
+    #[error("Denom {denom} is not allowed to be transferred")]
+    DenomNotAllowed { denom: String },
+    #[error("Contract balance of {balance}{denom} cannot cover a transfer of {required}{denom}")]
+    InsufficientBalance {
+        denom: String,
+        balance: Uint128,
+        required: Uint128,
+    },
+    #[error("Cannot send to {count} channels at once, must be 1 to {max}")]
+    InvalidChannelCount { count: usize, max: usize },
+    #[error("Cannot send {count} messages in one packet, the maximum is {max}")]
+    TooManyMsgs { count: usize, max: u32 },
+    #[error("Withdraw amount must not be empty")]
+    EmptyWithdraw {},
+    #[error("No drain proposed")]
+    NoDrainProposed {},
+    #[error("Drain nonce does not match")]
+    DrainNonceMismatch {},
+    #[error("Drain is locked until height {unlock_height}")]
+    DrainLocked { unlock_height: u64 },
+}
@@ -1,13 +1,21 @@
 use cosmwasm_std::{
-    entry_point, from_slice, to_binary, DepsMut, Env, IbcBasicResponse, IbcChannelCloseMsg,
-    IbcChannelConnectMsg, IbcChannelOpenMsg, IbcMsg, IbcOrder, IbcPacketAckMsg,
-    IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, Never, StdError, StdResult,
+    entry_point, from_slice, from_slice_named, to_binary, Binary, DepsMut, Env, IbcBasicResponse,
+    IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcMsg, IbcOrder,
+    IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, IbcTimeout,
+    IbcTimeoutBlock, Never, StdError, StdResult,
 };
 
+use sha2::{Digest, Sha256};
+
+use crate::coins::NormalizeCoins;
 use crate::ibc_msg::{
     AcknowledgementMsg, BalancesResponse, DispatchResponse, PacketMsg, WhoAmIResponse,
 };
-use crate::state::{accounts, AccountData};
+use crate::msg::TimeoutMode;
+use crate::state::{
+    accounts, config_read, reindex_remote_addr, sorted_index, sorted_index_key, who_am_i_retries,
+    who_am_i_retries_read, AccountData, Config,
+};
 
 pub const IBC_APP_VERSION: &str = "ibc-reflect-v1";
 
@@ -15,11 +23,38 @@ pub const IBC_APP_VERSION: &str = "ibc-reflect-v1";
 /// packets live one hour
 pub const PACKET_LIFETIME: u64 = 60 * 60;
 
+/// Block timeout `blocks_ahead` blocks after the current block height
+pub fn timeout_block(env: &Env, revision: u64, blocks_ahead: u64) -> IbcTimeoutBlock {
+    IbcTimeoutBlock {
+        revision,
+        height: env.block.height + blocks_ahead,
+    }
+}
+
+/// Timeout for all packets and transfers we send. Depending on `Config::timeout_mode`,
+/// this is `PACKET_LIFETIME` after the current block time and/or `Config::timeout_blocks`
+/// after the current block height.
+pub fn packet_timeout(env: &Env, cfg: &Config) -> IbcTimeout {
+    let timestamp = env.block.time.plus_seconds(PACKET_LIFETIME);
+    let block = || timeout_block(env, cfg.timeout_revision, cfg.timeout_blocks);
+    match cfg.timeout_mode {
+        TimeoutMode::Timestamp => IbcTimeout::with_timestamp(timestamp),
+        TimeoutMode::Block => IbcTimeout::with_block(block()),
+        TimeoutMode::Both => IbcTimeout::with_both(block(), timestamp),
+    }
+}
+
 #[entry_point]
 /// enforces ordering and versioing constraints
-pub fn ibc_channel_open(_deps: DepsMut, _env: Env, msg: IbcChannelOpenMsg) -> StdResult<()> {
-    let channel = msg.channel();
+pub fn ibc_channel_open(deps: DepsMut, _env: Env, msg: IbcChannelOpenMsg) -> StdResult<()> {
+    validate_channel(msg.channel(), msg.counterparty_version())?;
+    let cfg = config_read(deps.storage).load()?;
+    validate_counterparty_port(msg.channel(), &cfg)
+}
 
+/// Checks the order and version of the channel as well as the counterparty version
+/// if it is known (which is not the case for OpenInit)
+pub fn validate_channel(channel: &IbcChannel, counterparty_version: Option<&str>) -> StdResult<()> {
     if channel.order != IbcOrder::Ordered {
         return Err(StdError::generic_err("Only supports ordered channels"));
     }
@@ -30,7 +65,7 @@ pub fn ibc_channel_open(_deps: DepsMut, _env: Env, msg: IbcChannelOpenMsg) -> St
         )));
     }
 
-    if let Some(counter_version) = msg.counterparty_version() {
+    if let Some(counter_version) = counterparty_version {
         if counter_version != IBC_APP_VERSION {
             return Err(StdError::generic_err(format!(
                 "Counterparty version must be `{}`",
@@ -42,6 +77,20 @@ pub fn ibc_channel_open(_deps: DepsMut, _env: Env, msg: IbcChannelOpenMsg) -> St
     Ok(())
 }
 
+/// Checks the counterparty port id has the `Config::expected_port_prefix` if one is set
+pub fn validate_counterparty_port(channel: &IbcChannel, cfg: &Config) -> StdResult<()> {
+    if let Some(prefix) = &cfg.expected_port_prefix {
+        let port_id = &channel.counterparty_endpoint.port_id;
+        if !port_id.starts_with(prefix.as_str()) {
+            return Err(StdError::generic_err(format!(
+                "Counterparty port `{}` must start with `{}`",
+                port_id, prefix
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[entry_point]
 /// once it's established, we send a WhoAmI message
 pub fn ibc_channel_connect(
@@ -52,17 +101,28 @@ pub fn ibc_channel_connect(
     let channel = msg.channel();
 
     let channel_id = &channel.endpoint.channel_id;
+    let cfg = config_read(deps.storage).load()?;
+    validate_counterparty_port(channel, &cfg)?;
+
+    // refuse to overwrite a live account, this indicates a connect firing twice.
+    // an account without remote address never completed its WhoAmI and may be reconnected.
+    let existing = accounts(deps.storage).may_load(channel_id.as_bytes())?;
+    if existing.and_then(|acct| acct.remote_addr).is_some() {
+        return Err(StdError::generic_err("channel already connected"));
+    }
 
     // create an account holder the channel exists (not found if not registered)
     let data = AccountData::default();
     accounts(deps.storage).save(channel_id.as_bytes(), &data)?;
+    sorted_index(deps.storage).save(&sorted_index_key(channel_id), channel_id)?;
+    who_am_i_retries(deps.storage).remove(channel_id.as_bytes());
 
     // construct a packet to send
     let packet = PacketMsg::WhoAmI {};
     let msg = IbcMsg::SendPacket {
         channel_id: channel_id.clone(),
         data: to_binary(&packet)?,
-        timeout: env.block.time.plus_seconds(PACKET_LIFETIME).into(),
+        timeout: packet_timeout(&env, &cfg),
     };
 
     Ok(IbcBasicResponse::new()
@@ -82,7 +142,12 @@ pub fn ibc_channel_close(
 
     // remove the channel
     let channel_id = &channel.endpoint.channel_id;
+    if let Some(acct) = accounts(deps.storage).may_load(channel_id.as_bytes())? {
+        reindex_remote_addr(deps.storage, channel_id, acct.remote_addr.as_deref(), None)?;
+    }
     accounts(deps.storage).remove(channel_id.as_bytes());
+    sorted_index(deps.storage).remove(&sorted_index_key(channel_id));
+    who_am_i_retries(deps.storage).remove(channel_id.as_bytes());
 
     Ok(IbcBasicResponse::new()
         .add_attribute("action", "ibc_close")
@@ -90,18 +155,31 @@ pub fn ibc_channel_close(
 }
 
 #[entry_point]
-/// never should be called as the other side never sends packets
+/// never should be called as the other side never sends packets,
+/// so we acknowledge every packet with an error
 pub fn ibc_packet_receive(
     _deps: DepsMut,
     _env: Env,
     _packet: IbcPacketReceiveMsg,
 ) -> Result<IbcReceiveResponse, Never> {
     Ok(IbcReceiveResponse::new()
-        .set_ack(b"{}")
-        .add_attribute("action", "ibc_packet_ack"))
+        .set_ack(encode_ibc_error(
+            "this contract does not accept inbound packets",
+        ))
+        .add_attribute("action", "ibc_packet_receive")
+        .add_attribute("error", "unexpected inbound packet"))
+}
+
+// this encode an error or error message into a proper acknowledgement to the recevier
+fn encode_ibc_error(msg: impl Into<String>) -> Binary {
+    // this cannot error, unwrap to keep the interface simple
+    to_binary(&AcknowledgementMsg::<()>::Err(msg.into())).unwrap()
 }
 
 #[entry_point]
+/// Note that unlike `Response`, `IbcBasicResponse` has no `data` field, as the IBC
+/// module does not return anything for acknowledgements. Results of the ack handlers,
+/// like the updated balance, can only be observed via attributes, events or queries.
 pub fn ibc_packet_ack(
     deps: DepsMut,
     env: Env,
@@ -111,17 +189,32 @@ pub fn ibc_packet_ack(
     let caller = msg.original_packet.src.channel_id;
     // we need to parse the ack based on our request
     let packet: PacketMsg = from_slice(&msg.original_packet.data)?;
+    acknowledge_packet(deps, env, caller, packet, &msg.acknowledgement.data)
+}
+
+/// Processes the acknowledgement `ack` of a `packet` we sent over `caller`.
+/// Shared between `ibc_packet_ack` and the `TestInjectAck` execute message.
+pub fn acknowledge_packet(
+    deps: DepsMut,
+    env: Env,
+    caller: String,
+    packet: PacketMsg,
+    ack: &[u8],
+) -> StdResult<IbcBasicResponse> {
     match packet {
         PacketMsg::Dispatch { .. } => {
-            let res: AcknowledgementMsg<DispatchResponse> = from_slice(&msg.acknowledgement.data)?;
+            let res: AcknowledgementMsg<DispatchResponse> =
+                from_slice_named(ack, "DispatchResponse ack")?;
             acknowledge_dispatch(deps, caller, res)
         }
         PacketMsg::WhoAmI {} => {
-            let res: AcknowledgementMsg<WhoAmIResponse> = from_slice(&msg.acknowledgement.data)?;
+            let res: AcknowledgementMsg<WhoAmIResponse> =
+                from_slice_named(ack, "WhoAmIResponse ack")?;
             acknowledge_who_am_i(deps, caller, res)
         }
         PacketMsg::Balances {} => {
-            let res: AcknowledgementMsg<BalancesResponse> = from_slice(&msg.acknowledgement.data)?;
+            let res: AcknowledgementMsg<BalancesResponse> =
+                from_slice_named(ack, "BalancesResponse ack")?;
             acknowledge_balances(deps, env, caller, res)
         }
     }
@@ -139,14 +232,14 @@ fn acknowledge_dispatch(
 }
 
 // receive PacketMsg::WhoAmI response
-// store address info in accounts info
+// store address info in accounts info (see Config::allow_remote_addr_change for updates)
 fn acknowledge_who_am_i(
     deps: DepsMut,
     caller: String,
     ack: AcknowledgementMsg<WhoAmIResponse>,
 ) -> StdResult<IbcBasicResponse> {
     // ignore errors (but mention in log)
-    let WhoAmIResponse { account } = match ack {
+    let WhoAmIResponse { account, signature } = match ack {
         AcknowledgementMsg::Ok(res) => res,
         AcknowledgementMsg::Err(e) => {
             return Ok(IbcBasicResponse::new()
@@ -155,18 +248,47 @@ fn acknowledge_who_am_i(
         }
     };
 
-    accounts(deps.storage).update(caller.as_bytes(), |acct| -> StdResult<_> {
+    let cfg = config_read(deps.storage).load()?;
+    if let Some(pubkey) = cfg.counterparty_pubkey {
+        let signature =
+            signature.ok_or_else(|| StdError::generic_err("WhoAmI signature missing"))?;
+        // bind the account to the channel, such that the ack cannot be replayed on another one
+        let hash = Sha256::digest(format!("{}/{}", caller, account).as_bytes());
+        if !deps.api.secp256k1_verify(&hash, &signature, &pubkey)? {
+            return Err(StdError::generic_err("WhoAmI signature invalid"));
+        }
+    }
+
+    let allow_change = cfg.allow_remote_addr_change;
+    let mut old_addr = None;
+    let acct = accounts(deps.storage).update(caller.as_bytes(), |acct| -> StdResult<_> {
         match acct {
             Some(mut acct) => {
-                // set the account the first time
-                if acct.remote_addr.is_none() {
-                    acct.remote_addr = Some(account);
+                old_addr = acct.remote_addr.clone();
+                match &acct.remote_addr {
+                    // set the account the first time
+                    None => acct.remote_addr = Some(account),
+                    // the cached balance belongs to the old account
+                    Some(old_addr) if allow_change && *old_addr != account => {
+                        acct = AccountData {
+                            remote_addr: Some(account),
+                            ..AccountData::default()
+                        };
+                    }
+                    Some(_) => {}
                 }
                 Ok(acct)
             }
             None => Err(StdError::generic_err("no account to update")),
         }
     })?;
+    reindex_remote_addr(
+        deps.storage,
+        &caller,
+        old_addr.as_deref(),
+        acct.remote_addr.as_deref(),
+    )?;
+    who_am_i_retries(deps.storage).remove(caller.as_bytes());
 
     Ok(IbcBasicResponse::new().add_attribute("action", "acknowledge_who_am_i"))
 }
@@ -188,11 +310,14 @@ fn acknowledge_balances(
         }
     };
 
-    accounts(deps.storage).update(caller.as_bytes(), |acct| -> StdResult<_> {
+    let allow_change = config_read(deps.storage).load()?.allow_remote_addr_change;
+    let mut old_addr = None;
+    let acct = accounts(deps.storage).update(caller.as_bytes(), |acct| -> StdResult<_> {
         match acct {
             Some(acct) => {
+                old_addr = acct.remote_addr.clone();
                 if let Some(old_addr) = acct.remote_addr {
-                    if old_addr != account {
+                    if old_addr != account && !allow_change {
                         return Err(StdError::generic_err(format!(
                             "remote account changed from {} to {}",
                             old_addr, account
@@ -202,44 +327,98 @@ fn acknowledge_balances(
                 Ok(AccountData {
                     last_update_time: env.block.time,
                     remote_addr: Some(account),
-                    remote_balance: balances,
+                    remote_balance: balances.normalize(),
                 })
             }
             None => Err(StdError::generic_err("no account to update")),
         }
     })?;
+    reindex_remote_addr(
+        deps.storage,
+        &caller,
+        old_addr.as_deref(),
+        acct.remote_addr.as_deref(),
+    )?;
 
     Ok(IbcBasicResponse::new().add_attribute("action", "acknowledge_balances"))
 }
 
 #[entry_point]
-/// we just ignore these now. shall we store some info?
+/// WhoAmI packets are resent, as we cannot do anything with the channel without
+/// the remote address. All other timeouts are ignored.
 pub fn ibc_packet_timeout(
-    _deps: DepsMut,
-    _env: Env,
-    _msg: IbcPacketTimeoutMsg,
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
 ) -> StdResult<IbcBasicResponse> {
-    Ok(IbcBasicResponse::new().add_attribute("action", "ibc_packet_timeout"))
+    let packet: PacketMsg = from_slice(&msg.packet.data)?;
+    match packet {
+        PacketMsg::WhoAmI {} => retry_who_am_i(deps, env, msg.packet.src.channel_id),
+        _ => Ok(IbcBasicResponse::new().add_attribute("action", "ibc_packet_timeout")),
+    }
+}
+
+// resend a timed out WhoAmI until the channel's retries are used up
+fn retry_who_am_i(deps: DepsMut, env: Env, channel_id: String) -> StdResult<IbcBasicResponse> {
+    let res = IbcBasicResponse::new()
+        .add_attribute("action", "ibc_packet_timeout")
+        .add_attribute("channel_id", &channel_id);
+
+    // nothing to do if the channel was closed or the address is known by now
+    match accounts(deps.storage).may_load(channel_id.as_bytes())? {
+        Some(acct) if acct.remote_addr.is_none() => {}
+        _ => return Ok(res),
+    }
+
+    let cfg = config_read(deps.storage).load()?;
+    let retries = who_am_i_retries_read(deps.storage)
+        .may_load(channel_id.as_bytes())?
+        .unwrap_or_default();
+    if retries >= cfg.max_who_am_i_retries {
+        return Ok(res.add_attribute("error", "WhoAmI retries exhausted"));
+    }
+    who_am_i_retries(deps.storage).save(channel_id.as_bytes(), &(retries + 1))?;
+
+    let msg = IbcMsg::SendPacket {
+        channel_id,
+        data: to_binary(&PacketMsg::WhoAmI {})?,
+        timeout: packet_timeout(&env, &cfg),
+    };
+    Ok(res
+        .add_message(msg)
+        .add_attribute("retry", (retries + 1).to_string()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::contract::{execute, instantiate, query};
-    use crate::msg::{AccountResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::errors::ContractError;
+    use crate::msg::{
+        AccountByRemoteResponse, AccountResponse, ExecuteMsg, InstantiateMsg, ListAccountsResponse,
+        QueryMsg,
+    };
+    use crate::state::DEFAULT_TIMEOUT_BLOCKS;
 
     use cosmwasm_std::testing::{
-        mock_dependencies, mock_env, mock_ibc_channel_connect_ack, mock_ibc_channel_open_init,
-        mock_ibc_channel_open_try, mock_ibc_packet_ack, mock_info, MockApi, MockQuerier,
-        MockStorage,
+        mock_dependencies, mock_env, mock_ibc_channel, mock_ibc_channel_close_init,
+        mock_ibc_channel_connect_ack, mock_ibc_channel_open_init, mock_ibc_channel_open_try,
+        mock_ibc_packet_ack, mock_ibc_packet_recv, mock_ibc_packet_timeout, mock_info, MockApi,
+        MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{
+        coin, coins, BankMsg, Coin, CosmosMsg, Deps, HexBinary, IbcAcknowledgement, IbcPacket,
+        OwnedDeps, Response, SubMsg, Uint128,
     };
-    use cosmwasm_std::{coin, coins, BankMsg, CosmosMsg, IbcAcknowledgement, OwnedDeps};
 
     const CREATOR: &str = "creator";
 
     fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        setup_with(InstantiateMsg::default())
+    }
+
+    fn setup_with(msg: InstantiateMsg) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {};
         let info = mock_info(CREATOR, &[]);
         let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
@@ -274,6 +453,7 @@ mod tests {
         let packet = PacketMsg::WhoAmI {};
         let response = AcknowledgementMsg::Ok(WhoAmIResponse {
             account: account.into(),
+            signature: None,
         });
         let ack = IbcAcknowledgement::encode_json(&response).unwrap();
         let msg = mock_ibc_packet_ack(channel_id, &packet, ack).unwrap();
@@ -281,6 +461,42 @@ mod tests {
         assert_eq!(0, res.messages.len());
     }
 
+    /// A Dispatch packet of `msgs` as sent by us over `channel_id`
+    fn mock_dispatch_packet(channel_id: &str, msgs: Vec<CosmosMsg>) -> IbcPacket {
+        let packet = PacketMsg::Dispatch { msgs };
+        mock_ibc_packet_timeout(channel_id, &packet).unwrap().packet
+    }
+
+    /// A Balances packet as sent by us over `channel_id`
+    fn mock_balances_packet(channel_id: &str) -> IbcPacket {
+        mock_ibc_packet_timeout(channel_id, &PacketMsg::Balances {})
+            .unwrap()
+            .packet
+    }
+
+    /// The acknowledgement of `packet`, routed by its `src.channel_id`
+    fn mock_packet_ack(packet: IbcPacket, ack: IbcAcknowledgement) -> IbcPacketAckMsg {
+        let mut msg = mock_ibc_packet_ack(&packet.src.channel_id, &(), ack).unwrap();
+        msg.original_packet = packet;
+        msg
+    }
+
+    fn balances_response(
+        deps: DepsMut,
+        channel_id: &str,
+        account: impl Into<String>,
+        balances: Vec<Coin>,
+    ) {
+        let response = AcknowledgementMsg::Ok(BalancesResponse {
+            account: account.into(),
+            balances,
+        });
+        let ack = IbcAcknowledgement::encode_json(&response).unwrap();
+        let msg = mock_packet_ack(mock_balances_packet(channel_id), ack);
+        let res = ibc_packet_ack(deps, mock_env(), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+    }
+
     #[test]
     fn enforce_version_in_handshake() {
         let mut deps = setup();
@@ -297,6 +513,79 @@ mod tests {
         ibc_channel_open(deps.as_mut(), mock_env(), valid_handshake).unwrap();
     }
 
+    #[test]
+    fn validate_channel_works() {
+        let channel = mock_ibc_channel("channel-12", IbcOrder::Ordered, IBC_APP_VERSION);
+        validate_channel(&channel, None).unwrap();
+        validate_channel(&channel, Some(IBC_APP_VERSION)).unwrap();
+
+        let channel = mock_ibc_channel("channel-12", IbcOrder::Unordered, IBC_APP_VERSION);
+        let err = validate_channel(&channel, None).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Only supports ordered channels"));
+
+        let channel = mock_ibc_channel("channel-12", IbcOrder::Ordered, "reflect");
+        let err = validate_channel(&channel, None).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Must set version to `ibc-reflect-v1`")
+        );
+
+        let channel = mock_ibc_channel("channel-12", IbcOrder::Ordered, IBC_APP_VERSION);
+        let err = validate_channel(&channel, Some("reflect")).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Counterparty version must be `ibc-reflect-v1`")
+        );
+    }
+
+    #[test]
+    fn handshake_checks_expected_port_prefix() {
+        // disabled by default
+        let mut deps = setup();
+        let handshake_open =
+            mock_ibc_channel_open_init("channel-1", IbcOrder::Ordered, IBC_APP_VERSION);
+        ibc_channel_open(deps.as_mut(), mock_env(), handshake_open).unwrap();
+        connect(deps.as_mut(), "channel-1");
+
+        // matching prefix
+        let mut deps = setup_with(InstantiateMsg {
+            expected_port_prefix: Some("their".to_string()),
+            ..InstantiateMsg::default()
+        });
+        connect(deps.as_mut(), "channel-1");
+
+        // mismatched prefix
+        let mut deps = setup_with(InstantiateMsg {
+            expected_port_prefix: Some("wasm.".to_string()),
+            ..InstantiateMsg::default()
+        });
+        let expected_err =
+            || StdError::generic_err("Counterparty port `their_port` must start with `wasm.`");
+        let handshake_open =
+            mock_ibc_channel_open_init("channel-1", IbcOrder::Ordered, IBC_APP_VERSION);
+        let err = ibc_channel_open(deps.as_mut(), mock_env(), handshake_open).unwrap_err();
+        assert_eq!(err, expected_err());
+        let handshake_connect =
+            mock_ibc_channel_connect_ack("channel-1", IbcOrder::Ordered, IBC_APP_VERSION);
+        let err = ibc_channel_connect(deps.as_mut(), mock_env(), handshake_connect).unwrap_err();
+        assert_eq!(err, expected_err());
+
+        // the admin can disable the check
+        let msg = ExecuteMsg::UpdateExpectedPortPrefix {
+            expected_port_prefix: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        connect(deps.as_mut(), "channel-1");
+    }
+
     #[test]
     fn proper_handshake_flow() {
         // setup and connect handshake
@@ -307,6 +596,7 @@ mod tests {
         // check for empty account
         let q = QueryMsg::Account {
             channel_id: channel_id.into(),
+            max_age: None,
         };
         let r = query(deps.as_ref(), mock_env(), q).unwrap();
         let acct: AccountResponse = from_slice(&r).unwrap();
@@ -321,6 +611,7 @@ mod tests {
         // account should be set up
         let q = QueryMsg::Account {
             channel_id: channel_id.into(),
+            max_age: None,
         };
         let r = query(deps.as_ref(), mock_env(), q).unwrap();
         let acct: AccountResponse = from_slice(&r).unwrap();
@@ -329,6 +620,704 @@ mod tests {
         assert_eq!(0, acct.last_update_time.nanos());
     }
 
+    #[test]
+    fn duplicate_connect_on_live_channel_fails() {
+        let mut deps = setup();
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+
+        // connecting again before the WhoAmI ack is fine and resends WhoAmI
+        connect(deps.as_mut(), channel_id);
+
+        // once the remote address is known, the channel is live
+        who_am_i_response(deps.as_mut(), channel_id, "account-789");
+        let handshake_connect =
+            mock_ibc_channel_connect_ack(channel_id, IbcOrder::Ordered, IBC_APP_VERSION);
+        let err = ibc_channel_connect(deps.as_mut(), mock_env(), handshake_connect).unwrap_err();
+        assert_eq!(err, StdError::generic_err("channel already connected"));
+
+        // account is untouched
+        let q = QueryMsg::Account {
+            channel_id: channel_id.into(),
+            max_age: None,
+        };
+        let r = query(deps.as_ref(), mock_env(), q).unwrap();
+        let acct: AccountResponse = from_slice(&r).unwrap();
+        assert_eq!(acct.remote_addr.unwrap(), "account-789");
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_inject_ack_drives_who_am_i() {
+        let mut deps = setup();
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+
+        let ack = IbcAcknowledgement::encode_json(&AcknowledgementMsg::Ok(WhoAmIResponse {
+            account: "account-789".into(),
+            signature: None,
+        }))
+        .unwrap();
+        let msg = ExecuteMsg::TestInjectAck {
+            channel_id: channel_id.into(),
+            packet: PacketMsg::WhoAmI {},
+            ack: ack.data,
+        };
+
+        // only the admin can inject acks
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(res.attributes, vec![("action", "acknowledge_who_am_i")]);
+
+        // the account is set up just like with a relayed ack
+        let q = QueryMsg::Account {
+            channel_id: channel_id.into(),
+            max_age: None,
+        };
+        let r = query(deps.as_ref(), mock_env(), q).unwrap();
+        let acct: AccountResponse = from_slice(&r).unwrap();
+        assert_eq!(acct.remote_addr.unwrap(), "account-789");
+    }
+
+    #[test]
+    fn malformed_ack_error_names_packet_type() {
+        let mut deps = setup();
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+
+        for (packet, name) in [
+            (PacketMsg::WhoAmI {}, "WhoAmIResponse ack"),
+            (PacketMsg::Balances {}, "BalancesResponse ack"),
+        ] {
+            let ack = IbcAcknowledgement::new(br#"{"ok":{"foo":1}}"#);
+            let msg = mock_ibc_packet_ack(channel_id, &packet, ack).unwrap();
+            let err = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap_err();
+            match err {
+                StdError::ParseErr { target_type, .. } => assert_eq!(target_type, name),
+                err => panic!("Unexpected error: {:?}", err),
+            }
+        }
+    }
+
+    fn query_account(deps: Deps, channel_id: &str) -> AccountResponse {
+        let q = QueryMsg::Account {
+            channel_id: channel_id.into(),
+            max_age: None,
+        };
+        let r = query(deps, mock_env(), q).unwrap();
+        from_slice(&r).unwrap()
+    }
+
+    #[test]
+    fn who_am_i_keeps_first_remote_addr_by_default() {
+        let mut deps = setup();
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+        who_am_i_response(deps.as_mut(), channel_id, "account-789");
+        balances_response(
+            deps.as_mut(),
+            channel_id,
+            "account-789",
+            coins(100, "uatom"),
+        );
+
+        // a second WhoAmI with a different address is ignored
+        who_am_i_response(deps.as_mut(), channel_id, "account-other");
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_addr.unwrap(), "account-789");
+        assert_eq!(acct.remote_balance, coins(100, "uatom"));
+    }
+
+    #[test]
+    fn who_am_i_replaces_remote_addr_if_allowed() {
+        let mut deps = setup_with(InstantiateMsg {
+            allow_remote_addr_change: true,
+            ..InstantiateMsg::default()
+        });
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+        who_am_i_response(deps.as_mut(), channel_id, "account-789");
+        balances_response(
+            deps.as_mut(),
+            channel_id,
+            "account-789",
+            coins(100, "uatom"),
+        );
+
+        // the same address again changes nothing
+        who_am_i_response(deps.as_mut(), channel_id, "account-789");
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_addr.unwrap(), "account-789");
+        assert_eq!(acct.remote_balance, coins(100, "uatom"));
+
+        // a different address replaces the old one and resets the balance
+        who_am_i_response(deps.as_mut(), channel_id, "account-other");
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_addr.unwrap(), "account-other");
+        assert!(acct.remote_balance.is_empty());
+        assert_eq!(0, acct.last_update_time.nanos());
+
+        // balances for yet another address are accepted as well
+        balances_response(
+            deps.as_mut(),
+            channel_id,
+            "account-third",
+            coins(5, "uatom"),
+        );
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_addr.unwrap(), "account-third");
+        assert_eq!(acct.remote_balance, coins(5, "uatom"));
+    }
+
+    // private key 0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef
+    const COUNTERPARTY_PUBKEY: &str =
+        "02bb50e2d89a4ed70663d080659fe0ad4b9bc3e06c17a227433966cb59ceee020d";
+    // signature of sha256("channel-1234/account-789") by the key above
+    const ACCOUNT_SIGNATURE: &str = "fe81e4d941485f171bc13552bf4400eb8b39e19fc3145b2060abe0fb63fcde6d171dd59006a69b67b7e4c6231f90fa4da8c9889b66d54e12b23f67399a8ffd1a";
+
+    fn signed_who_am_i_response(
+        deps: DepsMut,
+        channel_id: &str,
+        account: &str,
+        signature: Option<&str>,
+    ) -> StdResult<IbcBasicResponse> {
+        let packet = PacketMsg::WhoAmI {};
+        let response = AcknowledgementMsg::Ok(WhoAmIResponse {
+            account: account.into(),
+            signature: signature.map(|sig| HexBinary::from_hex(sig).unwrap()),
+        });
+        let ack = IbcAcknowledgement::encode_json(&response).unwrap();
+        let msg = mock_ibc_packet_ack(channel_id, &packet, ack).unwrap();
+        ibc_packet_ack(deps, mock_env(), msg)
+    }
+
+    #[test]
+    fn who_am_i_accepts_valid_signature() {
+        let mut deps = setup_with(InstantiateMsg {
+            counterparty_pubkey: Some(HexBinary::from_hex(COUNTERPARTY_PUBKEY).unwrap()),
+            ..InstantiateMsg::default()
+        });
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+
+        signed_who_am_i_response(
+            deps.as_mut(),
+            channel_id,
+            "account-789",
+            Some(ACCOUNT_SIGNATURE),
+        )
+        .unwrap();
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_addr.unwrap(), "account-789");
+    }
+
+    #[test]
+    fn who_am_i_rejects_invalid_signature() {
+        let mut deps = setup_with(InstantiateMsg {
+            counterparty_pubkey: Some(HexBinary::from_hex(COUNTERPARTY_PUBKEY).unwrap()),
+            ..InstantiateMsg::default()
+        });
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+
+        // signature for a different account
+        let err = signed_who_am_i_response(
+            deps.as_mut(),
+            channel_id,
+            "account-forged",
+            Some(ACCOUNT_SIGNATURE),
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("WhoAmI signature invalid"));
+
+        // no signature
+        let err =
+            signed_who_am_i_response(deps.as_mut(), channel_id, "account-789", None).unwrap_err();
+        assert_eq!(err, StdError::generic_err("WhoAmI signature missing"));
+
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert!(acct.remote_addr.is_none());
+
+        // a valid signature cannot be replayed on another channel
+        let other_channel_id = "channel-5678";
+        connect(deps.as_mut(), other_channel_id);
+        let err = signed_who_am_i_response(
+            deps.as_mut(),
+            other_channel_id,
+            "account-789",
+            Some(ACCOUNT_SIGNATURE),
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("WhoAmI signature invalid"));
+        let acct = query_account(deps.as_ref(), other_channel_id);
+        assert!(acct.remote_addr.is_none());
+    }
+
+    fn channels_of_remote(deps: Deps, remote_addr: &str) -> Vec<String> {
+        let q = QueryMsg::AccountByRemote {
+            remote_addr: remote_addr.into(),
+        };
+        let r = query(deps, mock_env(), q).unwrap();
+        let res: AccountByRemoteResponse = from_slice(&r).unwrap();
+        res.channel_ids
+    }
+
+    #[test]
+    fn account_by_remote_works() {
+        let mut deps = setup_with(InstantiateMsg {
+            allow_remote_addr_change: true,
+            ..InstantiateMsg::default()
+        });
+        connect(deps.as_mut(), "channel-2");
+        connect(deps.as_mut(), "channel-1");
+        connect(deps.as_mut(), "channel-3");
+
+        // not known before WhoAmI
+        assert!(channels_of_remote(deps.as_ref(), "account-789").is_empty());
+
+        who_am_i_response(deps.as_mut(), "channel-2", "account-789");
+        who_am_i_response(deps.as_mut(), "channel-1", "account-789");
+        balances_response(deps.as_mut(), "channel-3", "account-other", vec![]);
+        assert_eq!(
+            channels_of_remote(deps.as_ref(), "account-789"),
+            vec!["channel-1", "channel-2"]
+        );
+        assert_eq!(
+            channels_of_remote(deps.as_ref(), "account-other"),
+            vec!["channel-3"]
+        );
+
+        // miss
+        assert!(channels_of_remote(deps.as_ref(), "account-unknown").is_empty());
+
+        // a changed address moves the entry
+        who_am_i_response(deps.as_mut(), "channel-2", "account-other");
+        assert_eq!(
+            channels_of_remote(deps.as_ref(), "account-789"),
+            vec!["channel-1"]
+        );
+        assert_eq!(
+            channels_of_remote(deps.as_ref(), "account-other"),
+            vec!["channel-2", "channel-3"]
+        );
+    }
+
+    #[test]
+    fn account_by_remote_is_cleaned_up_on_close() {
+        let mut deps = setup();
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+        who_am_i_response(deps.as_mut(), channel_id, "account-789");
+        assert_eq!(
+            channels_of_remote(deps.as_ref(), "account-789"),
+            vec![channel_id]
+        );
+
+        let close = mock_ibc_channel_close_init(channel_id, IbcOrder::Ordered, IBC_APP_VERSION);
+        ibc_channel_close(deps.as_mut(), mock_env(), close).unwrap();
+        assert!(channels_of_remote(deps.as_ref(), "account-789").is_empty());
+    }
+
+    #[test]
+    fn sorted_accounts_follow_connect_and_close() {
+        let mut deps = setup();
+        connect(deps.as_mut(), "channel-10");
+        connect(deps.as_mut(), "channel-9");
+        let sorted = |deps: Deps| -> Vec<String> {
+            let msg = QueryMsg::ListAccountsSorted {
+                start_after: None,
+                limit: None,
+            };
+            let res: ListAccountsResponse =
+                from_slice(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.accounts
+                .into_iter()
+                .map(|acct| acct.channel_id)
+                .collect()
+        };
+        assert_eq!(sorted(deps.as_ref()), vec!["channel-9", "channel-10"]);
+
+        let close = mock_ibc_channel_close_init("channel-9", IbcOrder::Ordered, IBC_APP_VERSION);
+        ibc_channel_close(deps.as_mut(), mock_env(), close).unwrap();
+        assert_eq!(sorted(deps.as_ref()), vec!["channel-10"]);
+    }
+
+    fn who_am_i_timeout(deps: DepsMut, channel_id: &str) -> IbcBasicResponse {
+        let msg = mock_ibc_packet_timeout(channel_id, &PacketMsg::WhoAmI {}).unwrap();
+        ibc_packet_timeout(deps, mock_env(), msg).unwrap()
+    }
+
+    #[test]
+    fn who_am_i_timeout_is_retried() {
+        let mut deps = setup();
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+
+        let res = who_am_i_timeout(deps.as_mut(), channel_id);
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(IbcMsg::SendPacket {
+                channel_id: channel_id.into(),
+                data: to_binary(&PacketMsg::WhoAmI {}).unwrap(),
+                timeout: packet_timeout(&mock_env(), &config_read(&deps.storage).load().unwrap()),
+            })]
+        );
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "ibc_packet_timeout"),
+                ("channel_id", channel_id),
+                ("retry", "1")
+            ]
+        );
+
+        // no retry once the address is known
+        who_am_i_response(deps.as_mut(), channel_id, "account-789");
+        let res = who_am_i_timeout(deps.as_mut(), channel_id);
+        assert!(res.messages.is_empty());
+
+        // other packets are not retried
+        let msg = mock_ibc_packet_timeout(channel_id, &PacketMsg::Balances {}).unwrap();
+        let res = ibc_packet_timeout(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(res.messages.is_empty());
+    }
+
+    #[test]
+    fn who_am_i_retries_are_capped() {
+        let mut deps = setup_with(InstantiateMsg {
+            max_who_am_i_retries: Some(2),
+            ..InstantiateMsg::default()
+        });
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+
+        for retry in ["1", "2"] {
+            let res = who_am_i_timeout(deps.as_mut(), channel_id);
+            assert_eq!(res.messages.len(), 1);
+            assert_eq!(res.attributes[2], ("retry", retry));
+        }
+
+        // the third timeout gives up
+        let res = who_am_i_timeout(deps.as_mut(), channel_id);
+        assert!(res.messages.is_empty());
+        assert_eq!(res.attributes[2], ("error", "WhoAmI retries exhausted"));
+
+        // a reconnect starts over
+        connect(deps.as_mut(), channel_id);
+        let res = who_am_i_timeout(deps.as_mut(), channel_id);
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn inbound_packets_are_acknowledged_with_error() {
+        let mut deps = setup();
+        let channel_id = "channel-1234";
+        connect(deps.as_mut(), channel_id);
+
+        let msg = mock_ibc_packet_recv(channel_id, &PacketMsg::WhoAmI {}).unwrap();
+        let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(res.messages.is_empty());
+        let ack: AcknowledgementMsg<()> = from_slice(&res.acknowledgement).unwrap();
+        assert_eq!(
+            ack,
+            AcknowledgementMsg::Err("this contract does not accept inbound packets".to_string())
+        );
+        let ack = IbcAcknowledgement::new(res.acknowledgement);
+        assert!(!ack.is_success().unwrap());
+    }
+
+    #[test]
+    fn packet_timeout_works() {
+        let env = mock_env();
+        let deps = setup();
+        let mut cfg = config_read(&deps.storage).load().unwrap();
+        assert_eq!(cfg.timeout_mode, TimeoutMode::Timestamp);
+        let timeout = packet_timeout(&env, &cfg);
+        assert_eq!(timeout.block(), None);
+        assert_eq!(
+            timeout.timestamp(),
+            Some(env.block.time.plus_seconds(PACKET_LIFETIME))
+        );
+
+        let expected_block = IbcTimeoutBlock {
+            revision: 0,
+            height: env.block.height + DEFAULT_TIMEOUT_BLOCKS,
+        };
+        cfg.timeout_mode = TimeoutMode::Block;
+        let timeout = packet_timeout(&env, &cfg);
+        assert_eq!(timeout.block(), Some(expected_block));
+        assert_eq!(timeout.timestamp(), None);
+
+        cfg.timeout_mode = TimeoutMode::Both;
+        let timeout = packet_timeout(&env, &cfg);
+        assert_eq!(timeout.block(), Some(expected_block));
+        assert_eq!(
+            timeout.timestamp(),
+            Some(env.block.time.plus_seconds(PACKET_LIFETIME))
+        );
+    }
+
+    #[test]
+    fn send_msgs_uses_configured_block_timeout() {
+        let channel_id = "channel-1234";
+        let mut deps = setup_with(InstantiateMsg {
+            timeout_mode: Some(TimeoutMode::Block),
+            timeout_blocks: Some(50),
+            timeout_revision: Some(4),
+            ..InstantiateMsg::default()
+        });
+        connect(deps.as_mut(), channel_id);
+
+        let mut env = mock_env();
+        env.block.height = 1000;
+        let msg = ExecuteMsg::SendMsgs {
+            channel_id: channel_id.into(),
+            msgs: vec![],
+        };
+        let res = execute(deps.as_mut(), env, mock_info(CREATOR, &[]), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        match &res.messages[0].msg {
+            CosmosMsg::Ibc(IbcMsg::SendPacket { timeout, .. }) => {
+                assert_eq!(
+                    timeout.block(),
+                    Some(IbcTimeoutBlock {
+                        revision: 4,
+                        height: 1050
+                    })
+                );
+                assert_eq!(timeout.timestamp(), None);
+            }
+            o => panic!("Unexpected message: {:?}", o),
+        };
+    }
+
+    #[test]
+    fn close_channel_msg_can_be_built() {
+        let channel_id = "channel-1234";
+
+        let mut deps = setup();
+        connect(deps.as_mut(), channel_id);
+
+        let msg: CosmosMsg = IbcMsg::CloseChannel {
+            channel_id: channel_id.into(),
+        }
+        .into();
+        let res = IbcBasicResponse::new().add_message(msg);
+        assert_eq!(1, res.messages.len());
+        match &res.messages[0].msg {
+            CosmosMsg::Ibc(IbcMsg::CloseChannel {
+                channel_id: close_channel,
+            }) => assert_eq!(close_channel.as_str(), channel_id),
+            o => panic!("Unexpected message: {:?}", o),
+        };
+
+        // the account is only removed once the chain calls back into ibc_channel_close
+        accounts(deps.as_mut().storage)
+            .load(channel_id.as_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn balances_ack_drops_zero_amounts() {
+        let channel_id = "channel-1234";
+        let remote_addr = "account-789";
+
+        let mut deps = setup();
+        connect(deps.as_mut(), channel_id);
+        who_am_i_response(deps.as_mut(), channel_id, remote_addr);
+
+        // only zero amounts result in an empty balance
+        balances_response(
+            deps.as_mut(),
+            channel_id,
+            remote_addr,
+            vec![coin(0, "uatom"), coin(0, "ustake")],
+        );
+        let q = QueryMsg::Account {
+            channel_id: channel_id.into(),
+            max_age: None,
+        };
+        let r = query(deps.as_ref(), mock_env(), q).unwrap();
+        let acct: AccountResponse = from_slice(&r).unwrap();
+        assert!(acct.remote_balance.is_empty());
+
+        // zero amounts are dropped, others are kept
+        balances_response(
+            deps.as_mut(),
+            channel_id,
+            remote_addr,
+            vec![coin(0, "uatom"), coin(123, "ustake")],
+        );
+        let q = QueryMsg::Account {
+            channel_id: channel_id.into(),
+            max_age: None,
+        };
+        let r = query(deps.as_ref(), mock_env(), q).unwrap();
+        let acct: AccountResponse = from_slice(&r).unwrap();
+        assert_eq!(acct.remote_balance, vec![coin(123, "ustake")]);
+    }
+
+    #[test]
+    fn balances_ack_updates_account() {
+        let channel_id = "channel-1234";
+        let remote_addr = "account-789";
+
+        let mut deps = setup();
+        connect(deps.as_mut(), channel_id);
+        who_am_i_response(deps.as_mut(), channel_id, remote_addr);
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert!(acct.remote_balance.is_empty());
+        assert_eq!(0, acct.last_update_time.nanos());
+
+        balances_response(
+            deps.as_mut(),
+            channel_id,
+            remote_addr,
+            vec![coin(123, "ustake"), coin(100, "uatom")],
+        );
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_addr.unwrap(), remote_addr);
+        assert_eq!(
+            acct.remote_balance,
+            vec![coin(123, "ustake"), coin(100, "uatom")]
+        );
+        assert_eq!(acct.last_update_time, mock_env().block.time);
+
+        // a later ack replaces the balance
+        balances_response(deps.as_mut(), channel_id, remote_addr, coins(7, "uatom"));
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_balance, coins(7, "uatom"));
+    }
+
+    #[test]
+    fn balances_ack_rejects_changed_account() {
+        let channel_id = "channel-1234";
+
+        let mut deps = setup();
+        connect(deps.as_mut(), channel_id);
+        who_am_i_response(deps.as_mut(), channel_id, "account-789");
+        balances_response(
+            deps.as_mut(),
+            channel_id,
+            "account-789",
+            coins(100, "uatom"),
+        );
+
+        let response = AcknowledgementMsg::Ok(BalancesResponse {
+            account: "account-other".into(),
+            balances: coins(5, "uatom"),
+        });
+        let ack = IbcAcknowledgement::encode_json(&response).unwrap();
+        let msg = mock_packet_ack(mock_balances_packet(channel_id), ack);
+        let err = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("remote account changed from account-789 to account-other")
+        );
+
+        // the account is unchanged
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_addr.unwrap(), "account-789");
+        assert_eq!(acct.remote_balance, coins(100, "uatom"));
+    }
+
+    #[test]
+    fn send_msgs_updates_balance_optimistically() {
+        let channel_id = "channel-1234";
+        let remote_addr = "account-789";
+
+        let mut deps = setup_with(InstantiateMsg {
+            optimistic_balance_updates: true,
+            ..InstantiateMsg::default()
+        });
+        connect(deps.as_mut(), channel_id);
+        who_am_i_response(deps.as_mut(), channel_id, remote_addr);
+        balances_response(
+            deps.as_mut(),
+            channel_id,
+            remote_addr,
+            vec![coin(100, "uatom"), coin(5, "ustake")],
+        );
+
+        let send = |amount: Vec<Coin>| ExecuteMsg::SendMsgs {
+            channel_id: channel_id.into(),
+            msgs: vec![BankMsg::Send {
+                to_address: "my-friend".into(),
+                amount,
+            }
+            .into()],
+        };
+        let info = mock_info(CREATOR, &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            send(vec![coin(30, "uatom"), coin(5, "ustake")]),
+        )
+        .unwrap();
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_balance, coins(70, "uatom"));
+
+        // spending more than the cached balance is rejected
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            send(coins(71, "uatom")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+
+        // denoms not in the cached balance are not checked
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            send(vec![coin(1, "ustake"), coin(20, "uatom")]),
+        )
+        .unwrap();
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_balance, coins(50, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, send(coins(7, "ibc/ABCD"))).unwrap();
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert_eq!(acct.remote_balance, coins(50, "uatom"));
+    }
+
+    #[test]
+    fn send_msgs_optimistically_before_first_balances_update() {
+        let channel_id = "channel-1234";
+
+        let mut deps = setup_with(InstantiateMsg {
+            optimistic_balance_updates: true,
+            ..InstantiateMsg::default()
+        });
+        connect(deps.as_mut(), channel_id);
+        who_am_i_response(deps.as_mut(), channel_id, "account-789");
+
+        // nothing is cached yet, so bank sends are not blocked
+        let msg = ExecuteMsg::SendMsgs {
+            channel_id: channel_id.into(),
+            msgs: vec![BankMsg::Send {
+                to_address: "my-friend".into(),
+                amount: coins(30, "uatom"),
+            }
+            .into()],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        let acct = query_account(deps.as_ref(), channel_id);
+        assert!(acct.remote_balance.is_empty());
+    }
+
     #[test]
     fn dispatch_message_send_and_ack() {
         let channel_id = "channel-1234";
@@ -342,29 +1331,33 @@ mod tests {
         who_am_i_response(deps.as_mut(), channel_id, remote_addr);
 
         // try to dispatch a message
-        let msgs_to_dispatch = vec![BankMsg::Send {
+        let msgs_to_dispatch: Vec<CosmosMsg> = vec![BankMsg::Send {
             to_address: "my-friend".into(),
             amount: coins(123456789, "uatom"),
         }
         .into()];
         let handle_msg = ExecuteMsg::SendMsgs {
             channel_id: channel_id.into(),
-            msgs: msgs_to_dispatch,
+            msgs: msgs_to_dispatch.clone(),
         };
         let info = mock_info(CREATOR, &[]);
-        let mut res = execute(deps.as_mut(), mock_env(), info, handle_msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, handle_msg).unwrap();
         assert_eq!(1, res.messages.len());
-        let msg = match res.messages.swap_remove(0).msg {
+        let packet = mock_dispatch_packet(channel_id, msgs_to_dispatch);
+        match &res.messages[0].msg {
             CosmosMsg::Ibc(IbcMsg::SendPacket {
-                channel_id, data, ..
+                channel_id: packet_channel,
+                data,
+                ..
             }) => {
-                let ack = IbcAcknowledgement::encode_json(&AcknowledgementMsg::Ok(())).unwrap();
-                let mut msg = mock_ibc_packet_ack(&channel_id, &1, ack).unwrap();
-                msg.original_packet.data = data;
-                msg
+                assert_eq!(packet_channel, &packet.src.channel_id);
+                assert_eq!(data, &packet.data);
             }
             o => panic!("Unexpected message: {:?}", o),
         };
+
+        let ack = IbcAcknowledgement::encode_json(&AcknowledgementMsg::Ok(())).unwrap();
+        let msg = mock_packet_ack(packet, ack);
         let res = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap();
         // no actions expected, but let's check the events to see it was dispatched properly
         assert_eq!(0, res.messages.len());
@@ -383,6 +1376,9 @@ mod tests {
         connect(deps.as_mut(), reflect_channel_id);
         // get feedback from WhoAmI packet
         who_am_i_response(deps.as_mut(), reflect_channel_id, remote_addr);
+        // the funds sent along are part of the contract balance
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(12344, "utrgd"));
 
         // let's try to send funds to a channel that doesn't exist
         let msg = ExecuteMsg::SendFunds {
@@ -390,7 +1386,13 @@ mod tests {
             transfer_channel_id: transfer_channel_id.into(),
         };
         let info = mock_info(CREATOR, &coins(12344, "utrgd"));
-        execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReflectChannelNotRegistered {
+                channel_id: "random-channel".into()
+            }
+        );
 
         // let's try with no sent funds in the message
         let msg = ExecuteMsg::SendFunds {
@@ -398,7 +1400,17 @@ mod tests {
             transfer_channel_id: transfer_channel_id.into(),
         };
         let info = mock_info(CREATOR, &[]);
-        execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NoFunds {});
+
+        // or more than one denom
+        let msg = ExecuteMsg::SendFunds {
+            reflect_channel_id: reflect_channel_id.into(),
+            transfer_channel_id: transfer_channel_id.into(),
+        };
+        let info = mock_info(CREATOR, &[coin(12344, "utrgd"), coin(5, "ustake")]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::MultipleDenoms {});
 
         // 3rd times the charm
         let msg = ExecuteMsg::SendFunds {
@@ -424,4 +1436,358 @@ mod tests {
             o => panic!("unexpected message: {:?}", o),
         }
     }
+
+    #[test]
+    fn send_remote_funds_checks_allowed_denoms() {
+        let reflect_channel_id = "channel-1234";
+        let remote_addr = "account-789";
+        let transfer_channel_id = "transfer-2";
+
+        let mut deps = setup_with(InstantiateMsg {
+            allowed_denoms: Some(vec!["ucosm".to_string(), "utrgd".to_string()]),
+            ..InstantiateMsg::default()
+        });
+        connect(deps.as_mut(), reflect_channel_id);
+        who_am_i_response(deps.as_mut(), reflect_channel_id, remote_addr);
+        deps.querier.update_balance(
+            MOCK_CONTRACT_ADDR,
+            vec![coin(12344, "ustake"), coin(12344, "utrgd")],
+        );
+        let msg = ExecuteMsg::SendFunds {
+            reflect_channel_id: reflect_channel_id.into(),
+            transfer_channel_id: transfer_channel_id.into(),
+        };
+
+        // allowed denom passes
+        let info = mock_info(CREATOR, &coins(12344, "utrgd"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        // other denom is rejected
+        let info = mock_info(CREATOR, &coins(12344, "ustake"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DenomNotAllowed {
+                denom: "ustake".to_string()
+            }
+        );
+
+        // only the admin can change the list
+        let update = ExecuteMsg::UpdateAllowedDenoms {
+            allowed_denoms: Some(vec!["ustake".to_string()]),
+        };
+        let info = mock_info("random", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, update.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let info = mock_info(CREATOR, &[]);
+        execute(deps.as_mut(), mock_env(), info, update).unwrap();
+
+        let info = mock_info(CREATOR, &coins(12344, "ustake"));
+        execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+        let info = mock_info(CREATOR, &coins(12344, "utrgd"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DenomNotAllowed {
+                denom: "utrgd".to_string()
+            }
+        );
+
+        // an empty list allows all again
+        let update = ExecuteMsg::UpdateAllowedDenoms {
+            allowed_denoms: Some(vec![]),
+        };
+        let info = mock_info(CREATOR, &[]);
+        execute(deps.as_mut(), mock_env(), info, update).unwrap();
+        let info = mock_info(CREATOR, &coins(12344, "utrgd"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn send_remote_funds_allows_all_denoms_by_default() {
+        let reflect_channel_id = "channel-1234";
+
+        let mut deps = setup();
+        connect(deps.as_mut(), reflect_channel_id);
+        who_am_i_response(deps.as_mut(), reflect_channel_id, "account-789");
+        let denoms = [
+            "ucosm",
+            "ustake",
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+        ];
+        deps.querier.update_balance(
+            MOCK_CONTRACT_ADDR,
+            denoms.iter().map(|denom| coin(5, *denom)).collect(),
+        );
+
+        for denom in denoms {
+            let msg = ExecuteMsg::SendFunds {
+                reflect_channel_id: reflect_channel_id.into(),
+                transfer_channel_id: "transfer-2".into(),
+            };
+            let info = mock_info(CREATOR, &coins(5, denom));
+            let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            assert_eq!(1, res.messages.len());
+        }
+    }
+
+    #[test]
+    fn send_remote_funds_does_not_credit_cached_balance() {
+        let reflect_channel_id = "channel-1234";
+        let remote_addr = "account-789";
+
+        let mut deps = setup_with(InstantiateMsg {
+            optimistic_balance_updates: true,
+            ..InstantiateMsg::default()
+        });
+        connect(deps.as_mut(), reflect_channel_id);
+        who_am_i_response(deps.as_mut(), reflect_channel_id, remote_addr);
+        balances_response(
+            deps.as_mut(),
+            reflect_channel_id,
+            remote_addr,
+            coins(100, "uatom"),
+        );
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(12344, "utrgd"));
+
+        let msg = ExecuteMsg::SendFunds {
+            reflect_channel_id: reflect_channel_id.into(),
+            transfer_channel_id: "transfer-2".into(),
+        };
+        let info = mock_info(CREATOR, &coins(12344, "utrgd"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // the remote denom is only known after the next Balances update
+        let acct = query_account(deps.as_ref(), reflect_channel_id);
+        assert_eq!(acct.remote_balance, coins(100, "uatom"));
+    }
+
+    #[test]
+    fn send_remote_funds_requires_contract_balance() {
+        let reflect_channel_id = "channel-1234";
+
+        let mut deps = setup();
+        connect(deps.as_mut(), reflect_channel_id);
+        who_am_i_response(deps.as_mut(), reflect_channel_id, "account-789");
+        // some of the sent funds were spent by an earlier message
+        deps.querier.update_balance(
+            MOCK_CONTRACT_ADDR,
+            vec![coin(100, "ucosm"), coin(5, "utrgd")],
+        );
+
+        let msg = ExecuteMsg::SendFunds {
+            reflect_channel_id: reflect_channel_id.into(),
+            transfer_channel_id: "transfer-2".into(),
+        };
+        let info = mock_info(CREATOR, &coins(12, "utrgd"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientBalance {
+                denom: "utrgd".to_string(),
+                balance: Uint128::new(5),
+                required: Uint128::new(12),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Contract balance of 5utrgd cannot cover a transfer of 12utrgd"
+        );
+
+        // a balance covering the amount is fine
+        let info = mock_info(CREATOR, &coins(100, "ucosm"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(1, res.messages.len());
+    }
+
+    #[test]
+    fn send_remote_funds_rejects_zero_amount() {
+        let reflect_channel_id = "channel-1234";
+
+        let mut deps = setup();
+        connect(deps.as_mut(), reflect_channel_id);
+        who_am_i_response(deps.as_mut(), reflect_channel_id, "account-789");
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(100, "utrgd"));
+
+        let msg = ExecuteMsg::SendFunds {
+            reflect_channel_id: reflect_channel_id.into(),
+            transfer_channel_id: "transfer-2".into(),
+        };
+        let info = mock_info(CREATOR, &coins(0, "utrgd"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::ZeroAmount {});
+        assert_eq!(err.to_string(), "cannot transfer zero amount");
+
+        // a positive amount still works
+        let info = mock_info(CREATOR, &coins(1, "utrgd"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(1, res.messages.len());
+    }
+
+    #[test]
+    fn send_remote_funds_with_channel_pair() {
+        let reflect_channel_id = "channel-1234";
+        let transfer_channel_id = "transfer-2";
+
+        let mut deps = setup();
+        connect(deps.as_mut(), reflect_channel_id);
+        who_am_i_response(deps.as_mut(), reflect_channel_id, "account-789");
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(12344, "utrgd"));
+
+        // nothing stored yet
+        let info = mock_info(CREATOR, &coins(12344, "utrgd"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::SendFundsDefault {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoChannelPair {});
+
+        // only the admin may set the pair
+        let msg = ExecuteMsg::SetChannelPair {
+            reflect_channel_id: reflect_channel_id.into(),
+            transfer_channel_id: transfer_channel_id.into(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "handle_set_channel_pair"),
+                ("reflect_channel_id", reflect_channel_id),
+                ("transfer_channel_id", transfer_channel_id),
+            ]
+        );
+
+        // the default form sends the same transfer as the explicit one
+        let info = mock_info("anyone", &coins(12344, "utrgd"));
+        let explicit = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::SendFunds {
+                reflect_channel_id: reflect_channel_id.into(),
+                transfer_channel_id: transfer_channel_id.into(),
+            },
+        )
+        .unwrap();
+        let default = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::SendFundsDefault {},
+        )
+        .unwrap();
+        assert_eq!(default.messages, explicit.messages);
+        assert!(matches!(
+            &default.messages[0].msg,
+            CosmosMsg::Ibc(IbcMsg::Transfer { channel_id, .. }) if channel_id == transfer_channel_id
+        ));
+    }
+
+    #[test]
+    fn refresh_all_sends_who_am_i_to_every_channel() {
+        let mut deps = setup();
+        let channels = ["channel-1", "channel-2", "channel-3"];
+        for channel_id in channels {
+            connect(deps.as_mut(), channel_id);
+        }
+        // also channels that already know their remote address
+        who_am_i_response(deps.as_mut(), "channel-2", "account-2");
+
+        // only the admin
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            ExecuteMsg::RefreshAll {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            ExecuteMsg::RefreshAll {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let expected: Vec<_> = channels
+            .into_iter()
+            .map(|channel_id| {
+                SubMsg::new(IbcMsg::SendPacket {
+                    channel_id: channel_id.into(),
+                    data: to_binary(&PacketMsg::WhoAmI {}).unwrap(),
+                    timeout: mock_env().block.time.plus_seconds(PACKET_LIFETIME).into(),
+                })
+            })
+            .collect();
+        assert_eq!(res.messages, expected);
+        assert_eq!(res.events.len(), 3);
+        // everything fit into one page
+        assert!(!res.attributes.iter().any(|attr| attr.key == "next"));
+    }
+
+    #[test]
+    fn refresh_all_works_in_pages() {
+        let mut deps = setup();
+        // more channels than fit into the largest page
+        let channels: Vec<_> = (0..45).map(|i| format!("channel-{:02}", i)).collect();
+        for channel_id in &channels {
+            connect(deps.as_mut(), channel_id);
+        }
+
+        let refresh = |deps: DepsMut, start_after: Option<String>, limit: Option<u32>| {
+            let msg = ExecuteMsg::RefreshAll { start_after, limit };
+            execute(deps, mock_env(), mock_info(CREATOR, &[]), msg).unwrap()
+        };
+        let refreshed = |res: &Response| -> Vec<String> {
+            res.messages
+                .iter()
+                .map(|msg| match &msg.msg {
+                    CosmosMsg::Ibc(IbcMsg::SendPacket { channel_id, .. }) => channel_id.clone(),
+                    o => panic!("unexpected message: {:?}", o),
+                })
+                .collect()
+        };
+        let next = |res: &Response| {
+            res.attributes
+                .iter()
+                .find(|attr| attr.key == "next")
+                .map(|attr| attr.value.clone())
+        };
+
+        // default limit
+        let res = refresh(deps.as_mut(), None, None);
+        assert_eq!(refreshed(&res), channels[..10]);
+        assert_eq!(next(&res).unwrap(), "channel-09");
+
+        // the limit is capped
+        let res = refresh(deps.as_mut(), next(&res), Some(100));
+        assert_eq!(refreshed(&res), channels[10..40]);
+        assert_eq!(next(&res).unwrap(), "channel-39");
+
+        let res = refresh(deps.as_mut(), next(&res), Some(100));
+        assert_eq!(refreshed(&res), channels[40..]);
+        assert_eq!(next(&res), None);
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Coin, ContractResult, CosmosMsg};
+use cosmwasm_std::{Coin, ContractResult, CosmosMsg, HexBinary};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -24,6 +24,13 @@ pub type DispatchResponse = ();
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct WhoAmIResponse {
     pub account: String,
+    /// 64 byte secp256k1 signature (r, s) of the counterparty over
+    /// sha256(channel_id + "/" + account), where `channel_id` is the id of the channel
+    /// end on the sending chain, i.e. the counterparty channel id from the receiver's view.
+    /// Channel ids are unique per chain, so this binds the account to a single channel.
+    /// Required if the contract is configured with a `counterparty_pubkey`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<HexBinary>,
 }
 
 /// This is the success response we send on ack for PacketMsg::Balance.
@@ -1,4 +1,6 @@
+pub mod coins;
 pub mod contract;
+pub mod errors;
 pub mod ibc;
 pub mod ibc_msg;
 pub mod msg;
@@ -1,11 +1,71 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, CosmosMsg, Empty, Timestamp};
+use cosmwasm_std::{Binary, Coin, CosmosMsg, Empty, HexBinary, IbcTimeoutBlock, Order, Timestamp};
+
+#[cfg(feature = "test-utils")]
+use crate::ibc_msg::PacketMsg;
 
 use crate::state::AccountData;
 
-/// This needs no info. Owner of the contract is whoever signed the InstantiateMsg.
+/// Owner of the contract is whoever signed the InstantiateMsg.
 #[cw_serde]
-pub struct InstantiateMsg {}
+#[derive(Default)]
+pub struct InstantiateMsg {
+    /// Whether a WhoAmI or Balances acknowledgement may replace the remote address
+    /// of an account, see `Config::allow_remote_addr_change`. Defaults to false.
+    #[serde(default)]
+    pub allow_remote_addr_change: bool,
+    /// If set, WhoAmI acknowledgements must be signed with this compressed or
+    /// uncompressed secp256k1 public key, see `Config::counterparty_pubkey`.
+    #[serde(default)]
+    pub counterparty_pubkey: Option<HexBinary>,
+    /// How often a timed out WhoAmI packet is resent per channel.
+    /// Defaults to `DEFAULT_MAX_WHO_AM_I_RETRIES`.
+    #[serde(default)]
+    pub max_who_am_i_retries: Option<u32>,
+    /// Denoms SendFunds accepts, see `Config::allowed_denoms`. Defaults to all.
+    #[serde(default)]
+    pub allowed_denoms: Option<Vec<String>>,
+    /// Which timeouts the packets we send get. Defaults to timestamp only.
+    #[serde(default)]
+    pub timeout_mode: Option<TimeoutMode>,
+    /// Number of blocks after the current height used for block timeouts.
+    /// Defaults to `DEFAULT_TIMEOUT_BLOCKS`.
+    #[serde(default)]
+    pub timeout_blocks: Option<u64>,
+    /// Revision of the block timeouts. Defaults to 0.
+    #[serde(default)]
+    pub timeout_revision: Option<u64>,
+    /// Maximum number of messages SendMsgs accepts at once.
+    /// Defaults to `DEFAULT_MAX_MSGS_PER_PACKET`.
+    #[serde(default)]
+    pub max_msgs_per_packet: Option<u32>,
+    /// Whether SendMsgs updates the cached remote balance right away,
+    /// see `Config::optimistic_balance_updates`. Defaults to false.
+    #[serde(default)]
+    pub optimistic_balance_updates: bool,
+    /// Prefix the counterparty port id must have, see `Config::expected_port_prefix`.
+    /// Defaults to no check.
+    #[serde(default)]
+    pub expected_port_prefix: Option<String>,
+}
+
+/// Which timeouts are set on the packets and transfers we send
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum TimeoutMode {
+    /// `PACKET_LIFETIME` after the current block time
+    Timestamp,
+    /// `Config::timeout_blocks` after the current block height
+    Block,
+    /// Both of the above, whichever is reached first
+    Both,
+}
+
+impl Default for TimeoutMode {
+    fn default() -> Self {
+        TimeoutMode::Timestamp
+    }
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -13,14 +73,46 @@ pub enum ExecuteMsg {
     UpdateAdmin {
         admin: String,
     },
+    /// Removes the admin permanently, such that nobody can use admin-only messages anymore.
+    /// Only the admin may do this.
+    RenounceAdmin {},
+    /// Sets the denoms SendFunds accepts. `None` or an empty list allows all.
+    UpdateAllowedDenoms {
+        allowed_denoms: Option<Vec<String>>,
+    },
+    /// Sets the prefix the counterparty port id of new channels must have.
+    /// `None` disables the check.
+    UpdateExpectedPortPrefix {
+        expected_port_prefix: Option<String>,
+    },
+    /// Sets the maximum number of messages SendMsgs accepts at once
+    UpdateMaxMsgsPerPacket {
+        max_msgs_per_packet: u32,
+    },
     SendMsgs {
         channel_id: String,
         // Note: we don't handle custom messages on remote chains
         msgs: Vec<CosmosMsg<Empty>>,
     },
+    /// Sends the same messages over each of the channels, one packet per channel.
+    /// Fails as a whole if any of the channels is unknown.
+    SendMsgsMulti {
+        channel_ids: Vec<String>,
+        msgs: Vec<CosmosMsg<Empty>>,
+    },
     CheckRemoteBalance {
         channel_id: String,
     },
+    /// Sends a WhoAmI packet over one page of channels, e.g. to recover accounts whose
+    /// WhoAmI retries were used up. Only the admin may do this.
+    /// The remote addresses are updated asynchronously once the acks arrive.
+    /// If the page is full, the `next` attribute contains the `start_after` of the next page.
+    RefreshAll {
+        /// Only channels after this channel id (ordered by bytes) are refreshed
+        start_after: Option<String>,
+        /// Maximum number of channels refreshed. Defaults to 10 and is capped to 30.
+        limit: Option<u32>,
+    },
     /// If you sent funds to this contract, it will attempt to ibc transfer them
     /// to the account on the remote side of this channel.
     /// If we don't have the address yet, this fails.
@@ -32,6 +124,37 @@ pub enum ExecuteMsg {
         /// It should connect to the same chain as the reflect_channel_id does
         transfer_channel_id: String,
     },
+    /// Stores the channels used by SendFundsDefault. Only the admin may do this.
+    SetChannelPair {
+        reflect_channel_id: String,
+        transfer_channel_id: String,
+    },
+    /// Like SendFunds but with the channels stored via SetChannelPair
+    SendFundsDefault {},
+    /// Sends native funds of this contract to the given address, e.g. to recover funds
+    /// that got stuck. Only the admin may do this.
+    Withdraw {
+        to_address: String,
+        amount: Vec<Coin>,
+    },
+    /// Starts draining all native funds of this contract to the admin.
+    /// The returned `nonce` attribute is required to execute the drain.
+    ProposeDrain {},
+    /// Sends all native funds of this contract to the admin. Only possible
+    /// with the nonce of the latest proposal and `DRAIN_DELAY_BLOCKS` after it.
+    /// The nonce is a sequence number, not a secret. Changing the admin cancels the proposal.
+    ExecuteDrain {
+        nonce: u64,
+    },
+    /// Processes `ack` as the acknowledgement of `packet` sent over `channel_id`,
+    /// exactly like `ibc_packet_ack` would. Only the admin may do this.
+    /// Only for tests and local development, this is not available in production builds.
+    #[cfg(feature = "test-utils")]
+    TestInjectAck {
+        channel_id: String,
+        packet: PacketMsg,
+        ack: Binary,
+    },
 }
 
 #[cw_serde]
@@ -40,24 +163,135 @@ pub enum QueryMsg {
     // Returns current admin
     #[returns(AdminResponse)]
     Admin {},
-    // Shows all open accounts (incl. remote info)
+    // Shows all open accounts (incl. remote info), ordered by the bytes of the channel id.
+    // This means `channel-10` comes before `channel-2`, see `ListAccountsSorted`.
+    #[returns(ListAccountsResponse)]
+    ListAccounts {
+        /// Only accounts after this channel id (in the given order) are returned
+        start_after: Option<String>,
+        /// Maximum number of accounts returned. Defaults to 10 and is capped to 30.
+        limit: Option<u32>,
+        /// Order by channel id. Defaults to ascending.
+        order: Option<Order>,
+    },
+    // Like `ListAccounts`, but ordered by channel number, i.e. `channel-2` comes before
+    // `channel-10`. Ids without a numeric suffix are ordered by their bytes.
     #[returns(ListAccountsResponse)]
-    ListAccounts {},
+    ListAccountsSorted {
+        /// Only accounts after this channel id (in channel number order) are returned
+        start_after: Option<String>,
+        /// Maximum number of accounts returned. Defaults to 10 and is capped to 30.
+        limit: Option<u32>,
+    },
+    // Shows a compact summary of all open accounts, ordered by the bytes of the channel id
+    #[returns(ListAccountSummariesResponse)]
+    ListAccountSummaries {
+        /// Only accounts after this channel id are returned
+        start_after: Option<String>,
+        /// Maximum number of accounts returned. Defaults to 10 and is capped to 30.
+        limit: Option<u32>,
+    },
     // Get account for one channel
-    #[returns(AccountInfo)]
-    Account { channel_id: String },
+    #[returns(AccountResponse)]
+    Account {
+        channel_id: String,
+        /// If set, the response tells if the cached balance is older than this many seconds
+        max_age: Option<u64>,
+    },
+    // Returns the channel ids bound to the given remote address
+    #[returns(AccountByRemoteResponse)]
+    AccountByRemote { remote_addr: String },
+    // Returns the channels whose remote address is not known yet, out of one page of
+    // accounts ordered by the bytes of the channel id. A page can thus contain fewer
+    // channels than `limit`, or none at all, while `next` is still set.
+    #[returns(PendingAccountsResponse)]
+    PendingAccounts {
+        /// Only accounts after this channel id (ordered by bytes) are looked at
+        start_after: Option<String>,
+        /// Maximum number of accounts looked at. Defaults to 10 and is capped to 30.
+        limit: Option<u32>,
+    },
+    // Returns the packet SendMsgs would send for these msgs, without sending it
+    #[returns(PreviewDispatchResponse)]
+    PreviewDispatch {
+        channel_id: String,
+        msgs: Vec<CosmosMsg<Empty>>,
+    },
+    // Sum of the cached remote balances of one page of accounts, ordered by the bytes of
+    // the channel id. Add up the pages to get the total of all accounts.
+    #[returns(TotalBalancesResponse)]
+    TotalBalances {
+        /// Only accounts after this channel id (ordered by bytes) are summed up
+        start_after: Option<String>,
+        /// Maximum number of accounts summed up. Defaults to 10 and is capped to 30.
+        limit: Option<u32>,
+    },
+    /// Returns the raw storage value at `key` (`None` if unset). Only for debugging,
+    /// this is not available in production builds.
+    #[cfg(feature = "debug")]
+    #[returns(Option<Binary>)]
+    Raw { key: Binary },
 }
 
 #[cw_serde]
 pub struct AdminResponse {
-    pub admin: String,
+    /// `None` if the admin was renounced
+    pub admin: Option<String>,
 }
 
 #[cw_serde]
 pub struct ListAccountsResponse {
     pub accounts: Vec<AccountInfo>,
+    /// The channel id to pass as `start_after` for the next page.
+    /// Set if this page is full, `None` on the last page.
+    pub next: Option<String>,
+}
+
+#[cw_serde]
+pub struct ListAccountSummariesResponse {
+    pub accounts: Vec<AccountSummary>,
+    /// The channel id to pass as `start_after` for the next page.
+    /// Set if this page is full, `None` on the last page.
+    pub next: Option<String>,
 }
 
+#[cw_serde]
+pub struct AccountByRemoteResponse {
+    /// Sorted channel ids, empty if the address is unknown
+    pub channel_ids: Vec<String>,
+}
+
+#[cw_serde]
+pub struct PendingAccountsResponse {
+    /// Sorted ids of the channels still waiting for a WhoAmI acknowledgement
+    pub channel_ids: Vec<String>,
+    /// The channel id to pass as `start_after` for the next page.
+    /// Set if this page is full, `None` on the last page.
+    pub next: Option<String>,
+}
+
+#[cw_serde]
+pub struct PreviewDispatchResponse {
+    /// The serialized `PacketMsg::Dispatch`
+    pub packet: Binary,
+    /// Timeout of the packet if it was sent in this block
+    pub timeout_timestamp: Option<Timestamp>,
+    /// Block timeout of the packet if it was sent in this block
+    pub timeout_block: Option<IbcTimeoutBlock>,
+}
+
+#[cw_serde]
+pub struct TotalBalancesResponse {
+    /// One coin per denom, sorted by denom
+    pub balances: Vec<Coin>,
+    /// The channel id to pass as `start_after` for the next page.
+    /// Set if this page is full, `None` on the last page.
+    pub next: Option<String>,
+}
+
+/// Fields are shared with `AccountInfo` of ibc-reflect, where `channel_id` has the
+/// same meaning. Unlike the reflect `account` there, `remote_addr` is optional here,
+/// since it is only known after the WhoAmI packet was acknowledged.
 #[cw_serde]
 pub struct AccountInfo {
     pub channel_id: String,
@@ -70,7 +304,7 @@ pub struct AccountInfo {
 }
 
 impl AccountInfo {
-    pub fn convert(channel_id: String, input: AccountData) -> Self {
+    pub fn new(channel_id: String, input: AccountData) -> Self {
         AccountInfo {
             channel_id,
             last_update_time: input.last_update_time,
@@ -78,6 +312,34 @@ impl AccountInfo {
             remote_balance: input.remote_balance,
         }
     }
+
+    #[deprecated(note = "Renamed to AccountInfo::new")]
+    pub fn convert(channel_id: String, input: AccountData) -> Self {
+        Self::new(channel_id, input)
+    }
+}
+
+/// A lean version of `AccountInfo` for listing many accounts
+#[cw_serde]
+pub struct AccountSummary {
+    pub channel_id: String,
+    /// false until the WhoAmI packet was acknowledged
+    pub has_remote_addr: bool,
+    /// number of denoms in the cached remote balance
+    pub denom_count: u32,
+    /// last block balance was updated (0 is never)
+    pub last_update_time: Timestamp,
+}
+
+impl From<(String, AccountData)> for AccountSummary {
+    fn from((channel_id, input): (String, AccountData)) -> Self {
+        AccountSummary {
+            channel_id,
+            has_remote_addr: input.remote_addr.is_some(),
+            denom_count: input.remote_balance.len() as u32,
+            last_update_time: input.last_update_time,
+        }
+    }
 }
 
 #[cw_serde]
@@ -88,6 +350,9 @@ pub struct AccountResponse {
     /// the channel and making a query and in that time it is empty
     pub remote_addr: Option<String>,
     pub remote_balance: Vec<Coin>,
+    /// Whether the balance was last updated more than `max_age` seconds ago.
+    /// Only set if `max_age` was given in the query.
+    pub stale: Option<bool>,
 }
 
 impl From<AccountData> for AccountResponse {
@@ -96,6 +361,136 @@ impl From<AccountData> for AccountResponse {
             last_update_time: input.last_update_time,
             remote_addr: input.remote_addr,
             remote_balance: input.remote_balance,
+            stale: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_schema::generate_api;
+    use cosmwasm_std::{coin, coins, from_slice, to_vec};
+
+    fn title(schema: &schemars::schema::RootSchema) -> &str {
+        schema
+            .schema
+            .metadata
+            .as_ref()
+            .and_then(|m| m.title.as_deref())
+            .unwrap()
+    }
+
+    #[test]
+    fn api_schema_covers_all_messages() {
+        let api = generate_api! {
+            instantiate: InstantiateMsg,
+            execute: ExecuteMsg,
+            query: QueryMsg,
+        };
+        assert_eq!(title(&api.instantiate), "InstantiateMsg");
+        assert_eq!(title(api.execute.as_ref().unwrap()), "ExecuteMsg");
+        assert_eq!(title(api.query.as_ref().unwrap()), "QueryMsg");
+        assert!(api.migrate.is_none());
+        assert!(api.sudo.is_none());
+
+        // every query has a response schema of the expected type
+        #[allow(unused_mut)]
+        let mut expected = vec![
+            ("account", "AccountResponse"),
+            ("account_by_remote", "AccountByRemoteResponse"),
+            ("admin", "AdminResponse"),
+            ("list_account_summaries", "ListAccountSummariesResponse"),
+            ("list_accounts", "ListAccountsResponse"),
+            ("list_accounts_sorted", "ListAccountsResponse"),
+            ("pending_accounts", "PendingAccountsResponse"),
+            ("preview_dispatch", "PreviewDispatchResponse"),
+        ];
+        #[cfg(feature = "debug")]
+        expected.push(("raw", "Nullable_Binary"));
+        expected.push(("total_balances", "TotalBalancesResponse"));
+        let responses: Vec<_> = api
+            .responses
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|(query, schema)| (query.as_str(), title(schema)))
+            .collect();
+        assert_eq!(responses, expected);
+
+        // rendering must not panic
+        api.render().to_string().unwrap();
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn account_info_convert_works() {
+        let data = AccountData {
+            last_update_time: Timestamp::from_seconds(1),
+            remote_addr: Some("account-789".to_string()),
+            remote_balance: coins(12, "ucosm"),
+        };
+        assert_eq!(
+            AccountInfo::convert("channel-7".to_string(), data.clone()),
+            AccountInfo::new("channel-7".to_string(), data)
+        );
+    }
+
+    #[test]
+    fn account_info_json_round_trips() {
+        let data = AccountData {
+            last_update_time: Timestamp::from_seconds(1),
+            remote_addr: Some("account-789".to_string()),
+            remote_balance: coins(12, "ucosm"),
+        };
+        let info = AccountInfo::new("channel-7".to_string(), data);
+        let json = to_vec(&info).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&json),
+            r#"{"channel_id":"channel-7","last_update_time":"1000000000","remote_addr":"account-789","remote_balance":[{"denom":"ucosm","amount":"12"}]}"#
+        );
+        let parsed: AccountInfo = from_slice(&json).unwrap();
+        assert_eq!(parsed, info);
+
+        // without remote address
+        let info = AccountInfo::new("channel-7".to_string(), AccountData::default());
+        let json = to_vec(&info).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&json),
+            r#"{"channel_id":"channel-7","last_update_time":"0","remote_addr":null,"remote_balance":[]}"#
+        );
+        let parsed: AccountInfo = from_slice(&json).unwrap();
+        assert_eq!(parsed, info);
+    }
+
+    #[test]
+    fn account_summary_from_account_data() {
+        let data = AccountData {
+            last_update_time: Timestamp::from_seconds(1),
+            remote_addr: Some("account-789".to_string()),
+            remote_balance: vec![coin(12, "ucosm"), coin(3, "ustake")],
+        };
+        let summary = AccountSummary::from(("channel-7".to_string(), data));
+        assert_eq!(
+            summary,
+            AccountSummary {
+                channel_id: "channel-7".to_string(),
+                has_remote_addr: true,
+                denom_count: 2,
+                last_update_time: Timestamp::from_seconds(1),
+            }
+        );
+
+        // without remote address and balances
+        let summary = AccountSummary::from(("channel-7".to_string(), AccountData::default()));
+        assert_eq!(
+            summary,
+            AccountSummary {
+                channel_id: "channel-7".to_string(),
+                has_remote_addr: false,
+                denom_count: 0,
+                last_update_time: Timestamp::from_nanos(0),
+            }
+        );
+    }
+}
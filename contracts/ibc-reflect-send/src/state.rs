@@ -1,17 +1,100 @@
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Coin, Storage, Timestamp};
+use cosmwasm_std::{Addr, Coin, HexBinary, StdResult, Storage, Timestamp};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
 };
 
+use crate::errors::ContractError;
+use crate::msg::TimeoutMode;
+
+pub const KEY_CHANNEL_PAIR: &[u8] = b"channel_pair";
 pub const KEY_CONFIG: &[u8] = b"config";
+pub const KEY_DRAIN_NONCE: &[u8] = b"drain_nonce";
+pub const KEY_PENDING_DRAIN: &[u8] = b"pending_drain";
 pub const PREFIX_ACCOUNTS: &[u8] = b"accounts";
+pub const PREFIX_REMOTE_INDEX: &[u8] = b"remote_index";
+pub const PREFIX_SORTED_INDEX: &[u8] = b"sorted_index";
+pub const PREFIX_WHO_AM_I_RETRIES: &[u8] = b"who_am_i_retries";
+
+/// default for `Config::max_who_am_i_retries`
+pub const DEFAULT_MAX_WHO_AM_I_RETRIES: u32 = 3;
+/// default for `Config::timeout_blocks`, about one hour with 5 second blocks
+pub const DEFAULT_TIMEOUT_BLOCKS: u64 = 720;
+/// default for `Config::max_msgs_per_packet`
+pub const DEFAULT_MAX_MSGS_PER_PACKET: u32 = 20;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Config {
-    pub admin: Addr,
+    /// `None` once the admin was renounced, which disables all admin-only messages
+    pub admin: Option<Addr>,
+    /// If false (the default), the first remote address received for an account is kept
+    /// and acknowledgements reporting a different address are ignored (WhoAmI) or
+    /// rejected (Balances). This is the safe mode, as the remote account bound to a
+    /// channel can never be swapped out under us.
+    ///
+    /// If true, a different address replaces the stored one and resets the cached balance.
+    /// Only enable this if the counterparty legitimately re-keys its accounts.
+    #[serde(default)]
+    pub allow_remote_addr_change: bool,
+    /// If set, a WhoAmI acknowledgement is only accepted with a valid signature of this key
+    /// over the channel id and the account (see `WhoAmIResponse::signature`), such that a
+    /// malicious relayer can neither forge the remote address nor replay it on another channel.
+    #[serde(default)]
+    pub counterparty_pubkey: Option<HexBinary>,
+    /// How often a timed out WhoAmI packet is resent per channel before giving up
+    #[serde(default = "default_max_who_am_i_retries")]
+    pub max_who_am_i_retries: u32,
+    /// If set and not empty, SendFunds only accepts coins of these denoms.
+    /// `None` or an empty list allows all denoms.
+    #[serde(default)]
+    pub allowed_denoms: Option<Vec<String>>,
+    /// Whether the packets and transfers we send time out by timestamp, block height or both
+    #[serde(default)]
+    pub timeout_mode: TimeoutMode,
+    /// Number of blocks after the current height used for block timeouts
+    #[serde(default = "default_timeout_blocks")]
+    pub timeout_blocks: u64,
+    /// Revision of the block timeouts
+    #[serde(default)]
+    pub timeout_revision: u64,
+    /// Maximum number of messages in a Dispatch packet, such that packets stay small
+    /// enough for relayers and the gas usage on the receiving side is bounded
+    #[serde(default = "default_max_msgs_per_packet")]
+    pub max_msgs_per_packet: u32,
+    /// If true, the bank sends dispatched via SendMsgs are subtracted from the cached
+    /// remote balance right away instead of waiting for the next Balances update.
+    /// Dispatches spending more than the cached balance of a denom are rejected in this mode.
+    /// Denoms not in the cached balance are not checked, since the cache does not know about
+    /// funds that arrived after the last update (e.g. via SendFunds), or might not be filled yet.
+    #[serde(default)]
+    pub optimistic_balance_updates: bool,
+    /// If set, channels are only opened and connected if the counterparty port id
+    /// starts with this prefix, e.g. `wasm.` for contracts. `None` disables the check.
+    #[serde(default)]
+    pub expected_port_prefix: Option<String>,
+}
+
+impl Config {
+    pub fn is_denom_allowed(&self, denom: &str) -> bool {
+        match &self.allowed_denoms {
+            Some(denoms) if !denoms.is_empty() => denoms.iter().any(|d| d == denom),
+            _ => true,
+        }
+    }
+}
+
+fn default_max_who_am_i_retries() -> u32 {
+    DEFAULT_MAX_WHO_AM_I_RETRIES
+}
+
+fn default_timeout_blocks() -> u64 {
+    DEFAULT_TIMEOUT_BLOCKS
+}
+
+fn default_max_msgs_per_packet() -> u32 {
+    DEFAULT_MAX_MSGS_PER_PACKET
 }
 
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
@@ -27,6 +110,26 @@ pub struct AccountData {
     pub remote_balance: Vec<Coin>,
 }
 
+/// A drain proposed by the admin, which can be executed once the timelock passed.
+/// It is cancelled when the admin changes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PendingDrain {
+    /// must be provided when executing the drain. This is a sequence number to tell
+    /// proposals apart, not a secret: it is emitted as an event attribute and easy to guess.
+    pub nonce: u64,
+    /// first block height at which the drain can be executed
+    pub unlock_height: u64,
+}
+
+/// The channels SendFundsDefault uses, as set by the admin
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChannelPair {
+    /// the channel to the reflect contract, which resolves the remote address
+    pub reflect_channel_id: String,
+    /// the channel for ibctransfer to the same chain
+    pub transfer_channel_id: String,
+}
+
 /// accounts is lookup of channel_id to reflect contract
 pub fn accounts(storage: &mut dyn Storage) -> Bucket<AccountData> {
     bucket(storage, PREFIX_ACCOUNTS)
@@ -36,6 +139,109 @@ pub fn accounts_read(storage: &dyn Storage) -> ReadonlyBucket<AccountData> {
     bucket_read(storage, PREFIX_ACCOUNTS)
 }
 
+/// Returns a key for sorting channel ids by their number, which zero-pads the numeric
+/// suffix, e.g. `channel-2` becomes `channel-00000000000000000002`. Ids without a numeric
+/// suffix are returned as is.
+///
+/// Storage keys are ordered by bytes, which would put `channel-10` before `channel-2`.
+pub fn channel_sort_key(channel_id: &str) -> String {
+    match channel_id.rsplit_once('-') {
+        Some((prefix, number)) if number.bytes().all(|b| b.is_ascii_digit()) => {
+            match number.parse::<u64>() {
+                Ok(number) => format!("{}-{:020}", prefix, number),
+                Err(_) => channel_id.to_string(),
+            }
+        }
+        _ => channel_id.to_string(),
+    }
+}
+
+/// sorted_index contains the channel id of every account, keyed by `sorted_index_key`,
+/// such that accounts can be paginated in channel number order
+pub fn sorted_index(storage: &mut dyn Storage) -> Bucket<String> {
+    bucket(storage, PREFIX_SORTED_INDEX)
+}
+
+pub fn sorted_index_read(storage: &dyn Storage) -> ReadonlyBucket<String> {
+    bucket_read(storage, PREFIX_SORTED_INDEX)
+}
+
+/// The key of `channel_id` in the sorted index. This is `channel_sort_key` followed by
+/// the channel id itself, as different ids can share a sort key (e.g. `channel-2` and
+/// `channel-02`). The zero byte in between sorts before any character of a sort key.
+pub fn sorted_index_key(channel_id: &str) -> Vec<u8> {
+    let mut key = channel_sort_key(channel_id).into_bytes();
+    key.push(0);
+    key.extend_from_slice(channel_id.as_bytes());
+    key
+}
+
+/// Loads the account of `channel_id`. Unlike `accounts_read(storage).load(..)`, this
+/// fails with `ContractError::ChannelNotFound` instead of a `NotFound` error that
+/// mentions internal type names.
+pub fn load_account_or_err(
+    storage: &dyn Storage,
+    channel_id: &str,
+) -> Result<AccountData, ContractError> {
+    accounts_read(storage)
+        .may_load(channel_id.as_bytes())?
+        .ok_or_else(|| ContractError::ChannelNotFound {
+            channel_id: channel_id.to_string(),
+        })
+}
+
+/// remote_index is a lookup of remote address to the channel ids bound to it (sorted)
+pub fn remote_index(storage: &mut dyn Storage) -> Bucket<Vec<String>> {
+    bucket(storage, PREFIX_REMOTE_INDEX)
+}
+
+pub fn remote_index_read(storage: &dyn Storage) -> ReadonlyBucket<Vec<String>> {
+    bucket_read(storage, PREFIX_REMOTE_INDEX)
+}
+
+/// Moves `channel_id` in the remote index from the `old` to the `new` remote address.
+/// Must be called whenever the `remote_addr` of an account changes.
+pub fn reindex_remote_addr(
+    storage: &mut dyn Storage,
+    channel_id: &str,
+    old: Option<&str>,
+    new: Option<&str>,
+) -> StdResult<()> {
+    if old == new {
+        return Ok(());
+    }
+    if let Some(old) = old {
+        let mut channels = remote_index(storage)
+            .may_load(old.as_bytes())?
+            .unwrap_or_default();
+        channels.retain(|c| c != channel_id);
+        if channels.is_empty() {
+            remote_index(storage).remove(old.as_bytes());
+        } else {
+            remote_index(storage).save(old.as_bytes(), &channels)?;
+        }
+    }
+    if let Some(new) = new {
+        let mut channels = remote_index(storage)
+            .may_load(new.as_bytes())?
+            .unwrap_or_default();
+        if let Err(pos) = channels.binary_search_by(|c| c.as_str().cmp(channel_id)) {
+            channels.insert(pos, channel_id.to_string());
+        }
+        remote_index(storage).save(new.as_bytes(), &channels)?;
+    }
+    Ok(())
+}
+
+/// number of WhoAmI packets resent after a timeout, per channel
+pub fn who_am_i_retries(storage: &mut dyn Storage) -> Bucket<u32> {
+    bucket(storage, PREFIX_WHO_AM_I_RETRIES)
+}
+
+pub fn who_am_i_retries_read(storage: &dyn Storage) -> ReadonlyBucket<u32> {
+    bucket_read(storage, PREFIX_WHO_AM_I_RETRIES)
+}
+
 pub fn config(storage: &mut dyn Storage) -> Singleton<Config> {
     singleton(storage, KEY_CONFIG)
 }
@@ -43,3 +249,25 @@ pub fn config(storage: &mut dyn Storage) -> Singleton<Config> {
 pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<Config> {
     singleton_read(storage, KEY_CONFIG)
 }
+
+/// the last drain nonce used, such that nonces are never reused.
+/// Nonces are a public sequence (1, 2, 3, ...), the admin check protects the drain.
+pub fn drain_nonce(storage: &mut dyn Storage) -> Singleton<u64> {
+    singleton(storage, KEY_DRAIN_NONCE)
+}
+
+pub fn pending_drain(storage: &mut dyn Storage) -> Singleton<PendingDrain> {
+    singleton(storage, KEY_PENDING_DRAIN)
+}
+
+pub fn pending_drain_read(storage: &dyn Storage) -> ReadonlySingleton<PendingDrain> {
+    singleton_read(storage, KEY_PENDING_DRAIN)
+}
+
+pub fn channel_pair(storage: &mut dyn Storage) -> Singleton<ChannelPair> {
+    singleton(storage, KEY_CHANNEL_PAIR)
+}
+
+pub fn channel_pair_read(storage: &dyn Storage) -> ReadonlySingleton<ChannelPair> {
+    singleton_read(storage, KEY_CHANNEL_PAIR)
+}
@@ -27,7 +27,7 @@ use cosmwasm_std::{
 };
 use cosmwasm_vm::testing::{
     execute, ibc_channel_connect, ibc_channel_open, ibc_packet_ack, instantiate, mock_env,
-    mock_info, mock_instance, query, MockApi, MockQuerier, MockStorage,
+    mock_info, mock_instance, query, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
 };
 use cosmwasm_vm::{from_slice, Instance};
 
@@ -45,7 +45,7 @@ const DESERIALIZATION_LIMIT: usize = 20_000;
 
 fn setup() -> Instance<MockApi, MockStorage, MockQuerier> {
     let mut deps = mock_instance(WASM, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg::default();
     let info = mock_info(CREATOR, &[]);
     let res: Response = instantiate(&mut deps, mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
@@ -84,6 +84,7 @@ fn who_am_i_response(
     let packet = PacketMsg::WhoAmI {};
     let response = AcknowledgementMsg::Ok(WhoAmIResponse {
         account: account.into(),
+        signature: None,
     });
     let ack = IbcAcknowledgement::encode_json(&response).unwrap();
     let msg = mock_ibc_packet_ack(channel_id, &packet, ack).unwrap();
@@ -96,7 +97,7 @@ fn instantiate_works() {
     let mut deps = setup();
     let r = query(&mut deps, mock_env(), QueryMsg::Admin {}).unwrap();
     let admin: AdminResponse = from_slice(&r, DESERIALIZATION_LIMIT).unwrap();
-    assert_eq!(CREATOR, admin.admin.as_str());
+    assert_eq!(admin.admin.as_deref(), Some(CREATOR));
 }
 
 #[test]
@@ -120,6 +121,7 @@ fn get_account(
 ) -> AccountResponse {
     let msg = QueryMsg::Account {
         channel_id: channel_id.into(),
+        max_age: None,
     };
     let r = query(deps, mock_env(), msg).unwrap();
     from_slice(&r, DESERIALIZATION_LIMIT).unwrap()
@@ -220,7 +222,12 @@ fn send_remote_funds() {
     let info = mock_info(CREATOR, &[]);
     execute::<_, _, _, _, Empty>(&mut deps, mock_env(), info, msg).unwrap_err();
 
-    // 3rd times the charm
+    // 3rd times the charm (the funds sent along are part of the contract balance)
+    deps.with_querier(|querier| {
+        querier.update_balance(MOCK_CONTRACT_ADDR, coins(12344, "utrgd"));
+        Ok(())
+    })
+    .unwrap();
     let msg = ExecuteMsg::SendFunds {
         reflect_channel_id: reflect_channel_id.into(),
         transfer_channel_id: transfer_channel_id.into(),
@@ -104,10 +104,7 @@ pub fn query_list_accounts(deps: Deps) -> StdResult<ListAccountsResponse> {
         .range(None, None, Order::Ascending)
         .map(|item| {
             let (key, account) = item?;
-            Ok(AccountInfo {
-                account: account.into(),
-                channel_id: String::from_utf8(key)?,
-            })
+            Ok(AccountInfo::new(String::from_utf8(key)?, account))
         })
         .collect();
     Ok(ListAccountsResponse {
@@ -1,5 +1,5 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, ContractResult, CosmosMsg};
+use cosmwasm_std::{Addr, Coin, ContractResult, CosmosMsg};
 
 /// Just needs to know the code_id of a reflect contract to spawn sub-accounts
 #[cw_serde]
@@ -30,10 +30,22 @@ pub struct ListAccountsResponse {
     pub accounts: Vec<AccountInfo>,
 }
 
+/// Fields are shared with `AccountInfo` of ibc-reflect-send, where `channel_id` has the
+/// same meaning. The reflect `account` is always set here, since it is created before the
+/// channel is stored, while the remote address is optional on the sending side.
 #[cw_serde]
 pub struct AccountInfo {
-    pub account: String,
     pub channel_id: String,
+    pub account: String,
+}
+
+impl AccountInfo {
+    pub fn new(channel_id: String, account: Addr) -> Self {
+        AccountInfo {
+            channel_id,
+            account: account.into(),
+        }
+    }
 }
 
 #[cw_serde]
@@ -70,3 +82,33 @@ pub struct BalancesResponse {
     pub account: String,
     pub balances: Vec<Coin>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{from_slice, to_vec};
+
+    #[test]
+    fn account_info_new_works() {
+        let info = AccountInfo::new("channel-7".to_string(), Addr::unchecked("reflect-acct-1"));
+        assert_eq!(
+            info,
+            AccountInfo {
+                channel_id: "channel-7".to_string(),
+                account: "reflect-acct-1".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn account_info_json_round_trips() {
+        let info = AccountInfo::new("channel-7".to_string(), Addr::unchecked("reflect-acct-1"));
+        let json = to_vec(&info).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&json),
+            r#"{"channel_id":"channel-7","account":"reflect-acct-1"}"#
+        );
+        let parsed: AccountInfo = from_slice(&json).unwrap();
+        assert_eq!(parsed, info);
+    }
+}
@@ -188,8 +188,8 @@ fn proper_handshake_flow() {
     assert_eq!(
         &res.accounts[0],
         &AccountInfo {
-            account: REFLECT_ADDR.into(),
             channel_id: channel_id.to_string(),
+            account: REFLECT_ADDR.into(),
         }
     );
 
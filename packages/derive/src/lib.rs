@@ -51,20 +51,115 @@ use std::str::FromStr;
 ///
 /// where `InstantiateMsg`, `ExecuteMsg`, and `QueryMsg` are contract defined
 /// types that implement `DeserializeOwned + JsonSchema`.
+///
+/// Every entry point takes at least the `deps` argument, so this fails to compile:
+/// ```compile_fail
+/// # use cosmwasm_std::{entry_point, Response, StdError};
+/// #[entry_point]
+/// pub fn instantiate() -> Result<Response, StdError> {
+///     Ok(Response::new())
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn entry_point(_attr: TokenStream, mut item: TokenStream) -> TokenStream {
     let cloned = item.clone();
     let function = parse_macro_input!(cloned as syn::ItemFn);
+    match export_for(&function) {
+        Ok(new_code) => {
+            let entry = TokenStream::from_str(&new_code).unwrap();
+            item.extend(entry);
+        }
+        Err(err) => item.extend(TokenStream::from(err.to_compile_error())),
+    }
+    item
+}
+
+/// This macro generates a single `__entry_points` Wasm export, which returns a Region
+/// pointer to a JSON list of the given entry points and the number of region pointers
+/// they take, such that host tooling can enumerate the entry points of a contract.
+///
+/// Since `#[entry_point]` only sees one function at a time, the entry points must be
+/// listed explicitly, once per contract:
+/// ```
+/// # use cosmwasm_std::{
+/// #     entry_point, entry_points, DepsMut, Deps, Env, MessageInfo, QueryResponse, Response,
+/// #     StdError,
+/// # };
+/// #
+/// # type InstantiateMsg = ();
+/// # type QueryMsg = ();
+/// #
+/// #[entry_point]
+/// pub fn instantiate(
+///     deps: DepsMut,
+///     env: Env,
+///     info: MessageInfo,
+///     msg: InstantiateMsg,
+/// ) -> Result<Response, StdError> {
+/// #   Ok(Default::default())
+/// }
+///
+/// #[entry_point]
+/// pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<QueryResponse, StdError> {
+/// #   Ok(Default::default())
+/// }
+///
+/// // exports `[{"name":"instantiate","args":3},{"name":"query","args":2}]`
+/// entry_points!(instantiate, query);
+/// ```
+///
+/// Only the entry point names known to `cosmwasm_std` are accepted:
+/// ```compile_fail
+/// # use cosmwasm_std::entry_points;
+/// entry_points!(init, handle);
+/// ```
+#[proc_macro]
+pub fn entry_points(input: TokenStream) -> TokenStream {
+    let names = parse_macro_input!(
+        input with syn::punctuated::Punctuated::<syn::Ident, Token![,]>::parse_terminated
+    );
+    let names: Vec<syn::Ident> = names.into_iter().collect();
+    if names.is_empty() {
+        let err = syn::Error::new(
+            proc_macro::Span::call_site().into(),
+            "at least one entry point must be listed",
+        );
+        return TokenStream::from(err.to_compile_error());
+    }
+    match entry_points_code(&names) {
+        Ok(code) => TokenStream::from_str(&code).unwrap(),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
+
+/// Creates the code of the Wasm export for the annotated `function`.
+fn export_for(function: &syn::ItemFn) -> syn::Result<String> {
     let name = function.sig.ident.to_string();
-    // The first argument is `deps`, the rest is region pointers
-    let args = function.sig.inputs.len() - 1;
+    let args = region_ptr_count(function)?;
+    Ok(wasm_export_code(&name, args))
+}
 
+/// The number of region pointers the Wasm export of `function` takes.
+/// The first argument is `deps`, the rest is region pointers.
+fn region_ptr_count(function: &syn::ItemFn) -> syn::Result<usize> {
+    if function.sig.inputs.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &function.sig,
+            "entry points must take at least a `deps` argument",
+        ));
+    }
+    Ok(function.sig.inputs.len() - 1)
+}
+
+/// Creates the code of the Wasm export calling `cosmwasm_std::do_<name>` with
+/// `args` region pointers, which is appended to the annotated function.
+fn wasm_export_code(name: &str, args: usize) -> String {
     // E.g. "ptr0: u32, ptr1: u32, ptr2: u32, "
     let typed_ptrs = (0..args).fold(String::new(), |acc, i| format!("{}ptr{}: u32, ", acc, i));
     // E.g. "ptr0, ptr1, ptr2, "
     let ptrs = (0..args).fold(String::new(), |acc, i| format!("{}ptr{}, ", acc, i));
 
-    let new_code = format!(
+    format!(
         r##"
         #[cfg(target_arch = "wasm32")]
         mod __wasm_export_{name} {{ // new module to avoid conflict of function name
@@ -77,8 +172,231 @@ pub fn entry_point(_attr: TokenStream, mut item: TokenStream) -> TokenStream {
         name = name,
         typed_ptrs = typed_ptrs,
         ptrs = ptrs
-    );
-    let entry = TokenStream::from_str(&new_code).unwrap();
-    item.extend(entry);
-    item
+    )
+}
+
+/// The number of region pointers the `cosmwasm_std::do_<name>` export of a known
+/// entry point takes, or `None` for unknown names.
+fn known_region_ptr_count(name: &str) -> Option<usize> {
+    match name {
+        "instantiate" | "execute" => Some(3),
+        "migrate" | "sudo" | "reply" | "query" => Some(2),
+        "ibc_channel_open" | "ibc_channel_connect" | "ibc_channel_close" => Some(2),
+        "ibc_packet_receive" | "ibc_packet_ack" | "ibc_packet_timeout" => Some(2),
+        _ => None,
+    }
+}
+
+/// Creates the JSON list describing the entry points `names`, i.e. their names
+/// and the number of region pointers they take.
+fn entry_points_json(names: &[syn::Ident]) -> syn::Result<String> {
+    let mut entries = Vec::with_capacity(names.len());
+    for (i, ident) in names.iter().enumerate() {
+        let name = ident.to_string();
+        if names[..i].contains(ident) {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("entry point `{}` is listed twice", name),
+            ));
+        }
+        let args = known_region_ptr_count(&name).ok_or_else(|| {
+            syn::Error::new(ident.span(), format!("unknown entry point `{}`", name))
+        })?;
+        entries.push(format!(r#"{{"name":"{}","args":{}}}"#, name, args));
+    }
+    Ok(format!("[{}]", entries.join(",")))
+}
+
+/// Creates the code of the `__entry_points` Wasm export listing `names`.
+fn entry_points_code(names: &[syn::Ident]) -> syn::Result<String> {
+    let json = entry_points_json(names)?;
+    // E.g. "instantiate, query, "
+    let functions = names
+        .iter()
+        .fold(String::new(), |acc, name| format!("{}{}, ", acc, name));
+
+    Ok(format!(
+        r##"
+        #[cfg(target_arch = "wasm32")]
+        #[no_mangle]
+        extern "C" fn __entry_points() -> u32 {{
+            // ensures all listed entry points exist
+            let _ = ({functions});
+            cosmwasm_std::do_entry_points(r#"{json}"#)
+        }}
+    "##,
+        functions = functions,
+        json = json
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `export_for` on the given entry point and returns the
+    /// `extern "C"` function in the generated module
+    fn parse_export(function: &str) -> syn::ItemFn {
+        let function: syn::ItemFn = syn::parse_str(function).unwrap();
+        let name = function.sig.ident.to_string();
+        let module: syn::ItemMod = syn::parse_str(&export_for(&function).unwrap()).unwrap();
+        assert_eq!(module.ident, format!("__wasm_export_{}", name));
+        let (_, items) = module.content.unwrap();
+        items
+            .into_iter()
+            .find_map(|item| match item {
+                syn::Item::Fn(function) if function.sig.ident == name => Some(function),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    /// The `cosmwasm_std::do_*` call in the body of the export
+    fn do_call(export: &syn::ItemFn) -> syn::ExprCall {
+        match export.block.stmts.as_slice() {
+            [syn::Stmt::Expr(syn::Expr::Call(call))] => call.clone(),
+            _ => panic!("export must consist of a single call"),
+        }
+    }
+
+    fn path_of(expr: &syn::Expr) -> String {
+        match expr {
+            syn::Expr::Path(path) => path
+                .path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::"),
+            _ => panic!("expected a path"),
+        }
+    }
+
+    #[test]
+    fn region_ptr_count_works() {
+        let function: syn::ItemFn = syn::parse_str(
+            "fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) {}",
+        )
+        .unwrap();
+        assert_eq!(region_ptr_count(&function).unwrap(), 3);
+
+        let function: syn::ItemFn = syn::parse_str("fn query(deps: Deps) {}").unwrap();
+        assert_eq!(region_ptr_count(&function).unwrap(), 0);
+    }
+
+    #[test]
+    fn region_ptr_count_fails_without_arguments() {
+        let function: syn::ItemFn = syn::parse_str("fn instantiate() {}").unwrap();
+        let err = region_ptr_count(&function).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "entry points must take at least a `deps` argument"
+        );
+    }
+
+    #[test]
+    fn export_for_works() {
+        // entry point signatures and the number of region pointers their exports take
+        // (`deps` is not passed in as a pointer)
+        for (function, name, args) in [
+            (
+                "pub fn instantiate(deps: DepsMut, env: Env, info: MessageInfo, msg: InstantiateMsg) -> StdResult<Response> { todo!() }",
+                "instantiate",
+                3,
+            ),
+            (
+                "pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> { todo!() }",
+                "execute",
+                3,
+            ),
+            (
+                "pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<QueryResponse> { todo!() }",
+                "query",
+                2,
+            ),
+            (
+                "pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> { todo!() }",
+                "migrate",
+                2,
+            ),
+            (
+                "pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> StdResult<Response> { todo!() }",
+                "reply",
+                2,
+            ),
+        ] {
+            let export = parse_export(function);
+            assert!(export.sig.abi.is_some());
+            assert_eq!(export.sig.inputs.len(), args);
+
+            let call = do_call(&export);
+            assert_eq!(path_of(&call.func), format!("cosmwasm_std::do_{}", name));
+            // the annotated function followed by all region pointers
+            assert_eq!(call.args.len(), args + 1);
+            match &call.args[0] {
+                syn::Expr::Reference(reference) => {
+                    assert_eq!(path_of(&reference.expr), format!("super::{}", name))
+                }
+                _ => panic!("expected a reference to the entry point"),
+            }
+            let ptrs: Vec<String> = call.args.iter().skip(1).map(path_of).collect();
+            let expected: Vec<String> = (0..args).map(|i| format!("ptr{}", i)).collect();
+            assert_eq!(ptrs, expected);
+        }
+    }
+
+    #[test]
+    fn export_for_fails_without_arguments() {
+        let function: syn::ItemFn =
+            syn::parse_str("pub fn instantiate() -> StdResult<Response> { todo!() }").unwrap();
+        let err = export_for(&function).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "entry points must take at least a `deps` argument"
+        );
+    }
+
+    fn idents(names: &[&str]) -> Vec<syn::Ident> {
+        names
+            .iter()
+            .map(|name| syn::parse_str(name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn entry_points_json_works() {
+        let json = entry_points_json(&idents(&["instantiate"])).unwrap();
+        assert_eq!(json, r#"[{"name":"instantiate","args":3}]"#);
+
+        let json =
+            entry_points_json(&idents(&["instantiate", "execute", "query", "reply"])).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"name":"instantiate","args":3},{"name":"execute","args":3},{"name":"query","args":2},{"name":"reply","args":2}]"#
+        );
+    }
+
+    #[test]
+    fn entry_points_json_fails_for_unknown_and_duplicate_names() {
+        let err = entry_points_json(&idents(&["instantiate", "handle"])).unwrap_err();
+        assert_eq!(err.to_string(), "unknown entry point `handle`");
+
+        let err = entry_points_json(&idents(&["query", "execute", "query"])).unwrap_err();
+        assert_eq!(err.to_string(), "entry point `query` is listed twice");
+    }
+
+    #[test]
+    fn entry_points_code_works() {
+        let code = entry_points_code(&idents(&["instantiate", "query"])).unwrap();
+        let export: syn::ItemFn = syn::parse_str(&code).unwrap();
+        assert_eq!(export.sig.ident, "__entry_points");
+        assert!(export.sig.abi.is_some());
+        assert!(export.sig.inputs.is_empty());
+        // the generated list contains the decorated functions
+        assert!(code.contains(r#""name":"instantiate""#));
+        assert!(code.contains(r#""name":"query""#));
+        assert!(code.contains(
+            r##"cosmwasm_std::do_entry_points(r#"[{"name":"instantiate","args":3},{"name":"query","args":2}]"#)"##
+        ));
+    }
 }
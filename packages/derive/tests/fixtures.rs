@@ -0,0 +1,9 @@
+//! Compiles small contracts using `#[entry_point]` on the host. The Wasm exports are
+//! only generated for wasm32, see the unit tests in src/lib.rs for their content.
+
+#[test]
+fn entry_point_fixtures() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/fixtures/pass/*.rs");
+    t.compile_fail("tests/fixtures/fail/*.rs");
+}
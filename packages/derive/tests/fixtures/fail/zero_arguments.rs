@@ -0,0 +1,8 @@
+use cosmwasm_std::{entry_point, Response, StdResult};
+
+#[entry_point]
+pub fn instantiate() -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn main() {}
@@ -1,5 +1,6 @@
 #[cfg(feature = "backtraces")]
 use std::backtrace::Backtrace;
+use std::convert::Infallible;
 use std::fmt;
 use thiserror::Error;
 
@@ -469,6 +470,16 @@ impl From<DivideByZeroError> for StdError {
     }
 }
 
+impl From<CheckedMultiplyFractionError> for StdError {
+    fn from(source: CheckedMultiplyFractionError) -> Self {
+        match source {
+            CheckedMultiplyFractionError::DivideByZero(err) => err.into(),
+            CheckedMultiplyFractionError::ConversionOverflow(err) => err.into(),
+            CheckedMultiplyFractionError::Overflow(err) => err.into(),
+        }
+    }
+}
+
 /// The return type for init, execute and query. Since the error type cannot be serialized to JSON,
 /// this is only available within the contract and its unit tests.
 ///
@@ -568,6 +579,12 @@ pub enum CheckedMultiplyFractionError {
     Overflow(#[from] OverflowError),
 }
 
+impl From<Infallible> for CheckedMultiplyFractionError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
+
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum CheckedMultiplyRatioError {
     #[error("Denominator must not be zero")]
@@ -795,6 +812,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_checked_multiply_fraction_error_works() {
+        let error: StdError =
+            CheckedMultiplyFractionError::from(DivideByZeroError::new(123u128)).into();
+        assert_eq!(
+            error,
+            StdError::divide_by_zero(DivideByZeroError::new(123u128))
+        );
+
+        let source = || OverflowError::new(OverflowOperation::Add, 3, 5);
+        let error: StdError = CheckedMultiplyFractionError::from(source()).into();
+        assert_eq!(error, StdError::overflow(source()));
+
+        let source = || ConversionOverflowError::new("Uint256", "Uint128", "1");
+        let error: StdError = CheckedMultiplyFractionError::from(source()).into();
+        assert_eq!(error, StdError::from(source()));
+    }
+
     #[test]
     fn implements_debug() {
         let error: StdError = StdError::from(OverflowError::new(OverflowOperation::Sub, 3, 5));
@@ -71,6 +71,13 @@ extern "C" fn deallocate(pointer: u32) {
     let _ = unsafe { consume_region(pointer as *mut Region) };
 }
 
+/// do_entry_points should be wrapped in an external "C" export, containing a JSON description
+/// of the contract's entry points. It returns a Region pointer to a copy of the given data.
+/// This is done via the `entry_points!` macro from cosmwasm-derive.
+pub fn do_entry_points(json: &'static str) -> u32 {
+    release_buffer(json.as_bytes().to_vec()) as u32
+}
+
 // TODO: replace with https://doc.rust-lang.org/std/ops/trait.Try.html once stabilized
 macro_rules! r#try_into_contract_result {
     ($expr:expr) => {
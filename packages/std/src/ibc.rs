@@ -3,6 +3,7 @@
 // The rest of the IBC related functionality is defined here
 
 use schemars::JsonSchema;
+use serde::de::IgnoredAny;
 use serde::{Deserialize, Serialize};
 use std::cmp::{Ord, Ordering, PartialOrd};
 
@@ -11,8 +12,8 @@ use crate::addresses::Addr;
 use crate::binary::Binary;
 use crate::coin::Coin;
 use crate::errors::StdResult;
-use crate::results::{Attribute, CosmosMsg, Empty, Event, SubMsg};
-use crate::serde::to_binary;
+use crate::results::{Attribute, ContractResult, CosmosMsg, Empty, Event, SubMsg};
+use crate::serde::{from_slice, to_binary};
 use crate::timestamp::Timestamp;
 
 /// These are messages in the IBC lifecycle. Only usable by IBC-enabled contracts
@@ -48,6 +49,11 @@ pub enum IbcMsg {
     },
     /// This will close an existing channel that is owned by this contract.
     /// Port is auto-assigned to the contract's IBC port
+    ///
+    /// Closing is asynchronous: the channel is only closed once the chain processed the message,
+    /// at which point the `ibc_channel_close` entry point of this contract is called with
+    /// `IbcChannelCloseMsg::CloseInit`. Any cleanup of contract state should happen there,
+    /// such that it is shared with closes initiated by the counterparty.
     CloseChannel { channel_id: String },
 }
 
@@ -244,6 +250,16 @@ impl IbcAcknowledgement {
             data: to_binary(data)?,
         })
     }
+
+    /// Checks if this is a success acknowledgement without parsing the success value.
+    ///
+    /// This assumes the acknowledgement is a JSON encoded [`ContractResult`], as created
+    /// by `IbcAcknowledgement::encode_json(&ContractResult::Ok(..))`. Other data results
+    /// in a parse error.
+    pub fn is_success(&self) -> StdResult<bool> {
+        let result: ContractResult<IgnoredAny> = from_slice(&self.data)?;
+        Ok(result.is_ok())
+    }
 }
 
 /// The message that is passed into `ibc_channel_open`
@@ -789,6 +805,77 @@ mod tests {
         assert_eq!(encoded.as_str(), expected);
     }
 
+    #[test]
+    fn serialize_close_channel_msg() {
+        let msg = IbcMsg::CloseChannel {
+            channel_id: "channel-123".to_string(),
+        };
+        let encoded = to_string(&msg).unwrap();
+        let expected = r#"{"close_channel":{"channel_id":"channel-123"}}"#;
+        assert_eq!(encoded.as_str(), expected);
+
+        let decoded: IbcMsg = serde_json_wasm::from_str(expected).unwrap();
+        assert_eq!(decoded, msg);
+
+        // wrapped into a CosmosMsg
+        let msg: CosmosMsg = msg.into();
+        let expected = r#"{"ibc":{"close_channel":{"channel_id":"channel-123"}}}"#;
+        assert_eq!(to_string(&msg).unwrap(), expected);
+    }
+
+    #[test]
+    fn ibc_acknowledgement_is_success_works() {
+        #[derive(Serialize)]
+        struct Data {
+            account: String,
+        }
+
+        let ack = IbcAcknowledgement::encode_json(&ContractResult::Ok(Data {
+            account: "foo".to_string(),
+        }))
+        .unwrap();
+        assert!(ack.is_success().unwrap());
+
+        let ack = IbcAcknowledgement::encode_json(&ContractResult::Ok(())).unwrap();
+        assert!(ack.is_success().unwrap());
+
+        let ack = IbcAcknowledgement::encode_json(&ContractResult::<()>::Err("broken".to_string()))
+            .unwrap();
+        assert!(!ack.is_success().unwrap());
+
+        // not a ContractResult
+        let ack = IbcAcknowledgement::new(b"{}".as_slice());
+        ack.is_success().unwrap_err();
+    }
+
+    #[test]
+    fn ibc_timeout_constructors_work() {
+        let block = IbcTimeoutBlock {
+            revision: 12,
+            height: 129,
+        };
+        let timestamp = Timestamp::from_nanos(684816844);
+
+        let timeout = IbcTimeout::with_timestamp(timestamp);
+        assert_eq!(timeout.block(), None);
+        assert_eq!(timeout.timestamp(), Some(timestamp));
+
+        let timeout = IbcTimeout::with_block(block);
+        assert_eq!(timeout.block(), Some(block));
+        assert_eq!(timeout.timestamp(), None);
+
+        let timeout = IbcTimeout::with_both(block, timestamp);
+        assert_eq!(timeout.block(), Some(block));
+        assert_eq!(timeout.timestamp(), Some(timestamp));
+
+        // conversions
+        assert_eq!(
+            IbcTimeout::from(timestamp),
+            IbcTimeout::with_timestamp(timestamp)
+        );
+        assert_eq!(IbcTimeout::from(block), IbcTimeout::with_block(block));
+    }
+
     #[test]
     fn ibc_timeout_serialize() {
         let timestamp = IbcTimeout::with_timestamp(Timestamp::from_nanos(684816844));
@@ -1,3 +1,6 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
 use crate::errors::StdError;
 
 /// A record of a key-value storage that is created through an iterator API.
@@ -6,7 +9,8 @@ use crate::errors::StdError;
 /// allows contracts to reuse the type when deserializing database records.
 pub type Record<V = Vec<u8>> = (Vec<u8>, V);
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
 // We assign these to integers to provide a stable API for passing over FFI (to wasm and Go)
 pub enum Order {
     Ascending = 1,
@@ -30,3 +34,20 @@ impl From<Order> for i32 {
         original as _
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_slice, to_vec};
+
+    #[test]
+    fn order_serde_works() {
+        assert_eq!(to_vec(&Order::Ascending).unwrap(), br#""ascending""#);
+        assert_eq!(to_vec(&Order::Descending).unwrap(), br#""descending""#);
+
+        let order: Order = from_slice(br#""ascending""#).unwrap();
+        assert_eq!(order, Order::Ascending);
+        let order: Order = from_slice(br#""descending""#).unwrap();
+        assert_eq!(order, Order::Descending);
+    }
+}
@@ -46,9 +46,10 @@ pub use crate::ibc::{
 };
 #[cfg(feature = "iterator")]
 pub use crate::iterator::{Order, Record};
+pub use crate::math::uint_hex;
 pub use crate::math::{
-    Decimal, Decimal256, Decimal256RangeExceeded, DecimalRangeExceeded, Fraction, Isqrt, Uint128,
-    Uint256, Uint512, Uint64,
+    compute_shares, fraction_to_permille, Decimal, Decimal256, Decimal256RangeExceeded,
+    DecimalRangeExceeded, Fraction, Isqrt, Uint128, Uint256, Uint512, Uint64,
 };
 pub use crate::never::Never;
 #[cfg(feature = "cosmwasm_1_2")]
@@ -79,7 +80,7 @@ pub use crate::results::{
 pub use crate::results::{DistributionMsg, StakingMsg};
 #[cfg(feature = "stargate")]
 pub use crate::results::{GovMsg, VoteOption};
-pub use crate::serde::{from_binary, from_slice, to_binary, to_vec};
+pub use crate::serde::{from_binary, from_slice, from_slice_named, to_binary, to_vec};
 pub use crate::storage::MemoryStorage;
 pub use crate::timestamp::Timestamp;
 pub use crate::traits::{Api, Querier, QuerierResult, QuerierWrapper, Storage};
@@ -95,7 +96,9 @@ mod imports;
 mod memory; // Used by exports and imports only. This assumes pointers are 32 bit long, which makes it untestable on dev machines.
 
 #[cfg(target_arch = "wasm32")]
-pub use crate::exports::{do_execute, do_instantiate, do_migrate, do_query, do_reply, do_sudo};
+pub use crate::exports::{
+    do_entry_points, do_execute, do_instantiate, do_migrate, do_query, do_reply, do_sudo,
+};
 #[cfg(all(feature = "stargate", target_arch = "wasm32"))]
 pub use crate::exports::{
     do_ibc_channel_close, do_ibc_channel_connect, do_ibc_channel_open, do_ibc_packet_ack,
@@ -111,4 +114,4 @@ pub mod testing;
 
 // Re-exports
 
-pub use cosmwasm_derive::entry_point;
+pub use cosmwasm_derive::{entry_point, entry_points};
@@ -409,6 +409,61 @@ impl Decimal {
             Uint128::one() + ((x - Uint128::one()) / y)
         }
     }
+
+    /// Formats this decimal with exactly `places` digits after the decimal point.
+    ///
+    /// If the value has more fractional digits than `places`, it is rounded half up,
+    /// i.e. to the nearest representable value with ties rounded away from zero.
+    /// Missing digits are padded with zeros. For `places == 0` no decimal point is written.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use cosmwasm_std::Decimal;
+    ///
+    /// assert_eq!(Decimal::one().to_string_fixed(2), "1.00");
+    ///
+    /// let d = Decimal::from_str("12.345").unwrap();
+    /// assert_eq!(d.to_string_fixed(2), "12.35");
+    /// assert_eq!(d.to_string_fixed(0), "12");
+    /// ```
+    pub fn to_string_fixed(&self, places: u8) -> String {
+        let places = places as u32;
+        let mut whole = (self.0 / Self::DECIMAL_FRACTIONAL).u128();
+        let fractional = (self.0)
+            .checked_rem(Self::DECIMAL_FRACTIONAL)
+            .unwrap()
+            .u128();
+
+        let fractional_string = if places >= Self::DECIMAL_PLACES {
+            format!(
+                "{:0>width$}{}",
+                fractional,
+                "0".repeat((places - Self::DECIMAL_PLACES) as usize),
+                width = Self::DECIMAL_PLACES as usize
+            )
+        } else {
+            let divisor = 10u128.pow(Self::DECIMAL_PLACES - places);
+            let mut digits = fractional / divisor;
+            // No overflow because the remainder is < 10^18
+            if (fractional % divisor) * 2 >= divisor {
+                digits += 1;
+            }
+            // Rounding up carried into the whole part
+            if digits == 10u128.pow(places) {
+                digits = 0;
+                whole += 1;
+            }
+            format!("{:0>width$}", digits, width = places as usize)
+        };
+
+        if places == 0 {
+            whole.to_string()
+        } else {
+            format!("{}.{}", whole, fractional_string)
+        }
+    }
 }
 
 impl Fraction<Uint128> for Decimal {
@@ -437,6 +492,17 @@ impl Fraction<Uint128> for Decimal {
     }
 }
 
+impl TryFrom<Uint128> for Decimal {
+    type Error = DecimalRangeExceeded;
+
+    /// Converts a whole number into a Decimal, e.g. 5 becomes 5.0.
+    ///
+    /// Fails for values larger than [`Decimal::MAX`]'s whole part.
+    fn try_from(value: Uint128) -> Result<Self, Self::Error> {
+        Self::from_atomics(value, 0)
+    }
+}
+
 impl FromStr for Decimal {
     type Err = StdError;
 
@@ -2105,6 +2171,75 @@ mod tests {
         assert_eq!(d.to_uint_ceil(), Uint128::new(340282366920938463464));
     }
 
+    #[test]
+    fn decimal_try_from_uint128_works() {
+        assert_eq!(
+            Decimal::try_from(Uint128::new(5)).unwrap(),
+            Decimal::percent(500)
+        );
+        assert_eq!(Decimal::try_from(Uint128::zero()).unwrap(), Decimal::zero());
+        assert_eq!(Decimal::try_from(Uint128::one()).unwrap(), Decimal::one());
+
+        // largest whole number that fits
+        let max_whole = Decimal::MAX.to_uint_floor();
+        assert_eq!(max_whole, Uint128::new(340282366920938463463));
+        assert_eq!(
+            Decimal::try_from(max_whole).unwrap(),
+            Decimal::from_str("340282366920938463463").unwrap()
+        );
+
+        // overflow
+        assert_eq!(
+            Decimal::try_from(max_whole + Uint128::one()),
+            Err(DecimalRangeExceeded)
+        );
+        assert_eq!(Decimal::try_from(Uint128::MAX), Err(DecimalRangeExceeded));
+    }
+
+    #[test]
+    fn decimal_to_string_fixed_works() {
+        // padding
+        assert_eq!(Decimal::one().to_string_fixed(2), "1.00");
+        assert_eq!(Decimal::zero().to_string_fixed(3), "0.000");
+        assert_eq!(Decimal::percent(50).to_string_fixed(4), "0.5000");
+        assert_eq!(dec("1.5").to_string_fixed(20), "1.50000000000000000000");
+        assert_eq!(
+            dec("0.000000000000000001").to_string_fixed(18),
+            "0.000000000000000001"
+        );
+
+        // no decimal point for zero places
+        assert_eq!(Decimal::one().to_string_fixed(0), "1");
+        assert_eq!(dec("12.345").to_string_fixed(0), "12");
+
+        // rounding half up
+        assert_eq!(dec("12.345").to_string_fixed(2), "12.35");
+        assert_eq!(dec("12.344999").to_string_fixed(2), "12.34");
+        assert_eq!(dec("0.5").to_string_fixed(0), "1");
+        assert_eq!(dec("0.05").to_string_fixed(1), "0.1");
+        assert_eq!(dec("2.675").to_string_fixed(2), "2.68");
+
+        // rounding carries into the whole part
+        assert_eq!(dec("9.999").to_string_fixed(2), "10.00");
+        assert_eq!(dec("0.96").to_string_fixed(1), "1.0");
+
+        // truncation of digits below the rounding threshold
+        assert_eq!(dec("3.14159").to_string_fixed(3), "3.142");
+        assert_eq!(dec("3.14149").to_string_fixed(3), "3.141");
+        assert_eq!(
+            dec("7.000000000000000001").to_string_fixed(17),
+            "7.00000000000000000"
+        );
+
+        // does not overflow for the max value
+        assert_eq!(Decimal::MAX.to_string_fixed(2), "340282366920938463463.37");
+        assert_eq!(Decimal::MAX.to_string_fixed(0), "340282366920938463463");
+        assert_eq!(
+            Decimal::MAX.to_string_fixed(18),
+            "340282366920938463463.374607431768211455"
+        );
+    }
+
     #[test]
     fn decimal_partial_eq() {
         let test_cases = [
@@ -15,7 +15,7 @@ use crate::{Decimal, Uint512};
 
 use super::Fraction;
 use super::Isqrt;
-use super::Uint256;
+use super::{Uint128, Uint256};
 
 /// A fixed-point decimal value with 18 fractional digits, i.e. Decimal256(1_000_000_000_000_000_000) == 1.0
 ///
@@ -462,6 +462,14 @@ impl From<Decimal> for Decimal256 {
     }
 }
 
+impl From<Uint128> for Decimal256 {
+    /// Converts a whole number into a Decimal256, e.g. 5 becomes 5.0.
+    fn from(value: Uint128) -> Self {
+        // Unwrap is safe because Uint128::MAX * 10^18 fits in Uint256.
+        Decimal256::from_atomics(value, 0).unwrap()
+    }
+}
+
 impl FromStr for Decimal256 {
     type Err = StdError;
 
@@ -987,6 +995,20 @@ mod tests {
         assert_eq!(b.to_string(), "340282366920938463463.374607431768211455");
     }
 
+    #[test]
+    fn decimal256_implements_from_uint128() {
+        let a = Decimal256::from(Uint128::new(5));
+        assert_eq!(a, Decimal256::percent(500));
+
+        let a = Decimal256::from(Uint128::zero());
+        assert_eq!(a, Decimal256::zero());
+
+        // lossless for values out of range of Decimal
+        let a = Decimal256::from(Uint128::MAX);
+        assert_eq!(a.to_string(), "340282366920938463463374607431768211455");
+        assert_eq!(a.to_uint_floor(), Uint256::from(Uint128::MAX));
+    }
+
     #[test]
     fn decimal256_from_str_works() {
         // Integers
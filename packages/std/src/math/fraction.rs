@@ -1,3 +1,6 @@
+use crate::errors::{CheckedMultiplyFractionError, DivideByZeroError};
+use crate::Uint128;
+
 /// A fraction `p`/`q` with integers `p` and `q`.
 ///
 /// `p` is called the numerator and `q` is called the denominator.
@@ -10,7 +13,23 @@ pub trait Fraction<T>: Sized {
     /// Returns the multiplicative inverse `q/p` for fraction `p/q`.
     ///
     /// If `p` is zero, None is returned.
+    ///
+    /// There is no default implementation since the trait cannot create an instance
+    /// from a numerator and denominator. Types with a fixed denominator, like
+    /// [`crate::Decimal`], need to compute the closest representable inverse anyways.
     fn inv(&self) -> Option<Self>;
+
+    /// Returns the multiplicative inverse `q/p` for fraction `p/q`.
+    ///
+    /// Like [`Fraction::inv`] but returns an error if `p` is zero, which makes it
+    /// possible to use `?`. Implementers only need to provide `inv`.
+    fn checked_inv(&self) -> Result<Self, DivideByZeroError>
+    where
+        T: ToString,
+    {
+        self.inv()
+            .ok_or_else(|| DivideByZeroError::new(self.denominator()))
+    }
 }
 
 impl<T: Copy + From<u8> + PartialEq> Fraction<T> for (T, T) {
@@ -31,6 +50,26 @@ impl<T: Copy + From<u8> + PartialEq> Fraction<T> for (T, T) {
     }
 }
 
+/// Returns the fraction `p`/`q` in permille, i.e. `p * 1000 / q` rounded down.
+/// This is useful for emitting ratios as integer attributes.
+///
+/// Returns an error if the denominator is zero or the result exceeds the `Uint128` range.
+///
+/// ## Examples
+///
+/// ```
+/// use cosmwasm_std::{fraction_to_permille, Decimal, Uint128};
+///
+/// assert_eq!(fraction_to_permille(&(1u128, 4u128)).unwrap(), Uint128::new(250));
+/// assert_eq!(fraction_to_permille(&Decimal::percent(12)).unwrap(), Uint128::new(120));
+/// ```
+pub fn fraction_to_permille<F: Fraction<T>, T: Into<Uint128>>(
+    fraction: &F,
+) -> Result<Uint128, CheckedMultiplyFractionError> {
+    let ratio = (fraction.numerator().into(), fraction.denominator().into());
+    Uint128::new(1000).checked_mul_floor(ratio)
+}
+
 #[macro_export]
 macro_rules! impl_mul_fraction {
     ($Uint:ident) => {
@@ -62,6 +101,15 @@ macro_rules! impl_mul_fraction {
                 self.checked_mul_floor(rhs).unwrap()
             }
 
+            /// Same operation as `checked_mul_floor` but returns a [`StdError`], such that
+            /// contracts can use `?` instead of panicking like `mul_floor` does.
+            pub fn try_mul_floor<F: Fraction<T>, T: Into<$Uint>>(
+                self,
+                rhs: F,
+            ) -> Result<Self, StdError> {
+                Ok(self.checked_mul_floor(rhs)?)
+            }
+
             /// Multiply `self` with a struct implementing [`Fraction`] (e.g. [`crate::Decimal`]).
             /// Result is rounded up.
             ///
@@ -93,6 +141,15 @@ macro_rules! impl_mul_fraction {
                 self.checked_mul_ceil(rhs).unwrap()
             }
 
+            /// Same operation as `checked_mul_ceil` but returns a [`StdError`], such that
+            /// contracts can use `?` instead of panicking like `mul_ceil` does.
+            pub fn try_mul_ceil<F: Fraction<T>, T: Into<$Uint>>(
+                self,
+                rhs: F,
+            ) -> Result<Self, StdError> {
+                Ok(self.checked_mul_ceil(rhs)?)
+            }
+
             /// Divide `self` with a struct implementing [`Fraction`] (e.g. [`crate::Decimal`]).
             /// Result is rounded down.
             ///
@@ -168,7 +225,9 @@ macro_rules! impl_mul_fraction {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Fraction, Uint128, Uint64};
+    use super::*;
+    use crate::errors::DivideByZeroError;
+    use crate::{Decimal, Fraction, Uint128, Uint64};
 
     #[test]
     fn fraction_tuple_methods() {
@@ -183,4 +242,64 @@ mod tests {
         let fraction = (Uint128::zero(), Uint128::one());
         assert_eq!(None, fraction.inv());
     }
+
+    #[test]
+    fn checked_inverse_works() {
+        let fraction = (Uint64::one(), Uint64::new(2));
+        assert_eq!(Ok((Uint64::new(2), Uint64::one())), fraction.checked_inv());
+
+        let fraction = (3u128, 7u128);
+        assert_eq!(Ok((7u128, 3u128)), fraction.checked_inv());
+
+        let decimal = Decimal::percent(50);
+        assert_eq!(Ok(Decimal::percent(200)), decimal.checked_inv());
+    }
+
+    #[test]
+    fn checked_inverse_with_zero_numerator() {
+        let fraction = (Uint128::zero(), Uint128::new(5));
+        assert_eq!(Err(DivideByZeroError::new(5u128)), fraction.checked_inv());
+
+        assert_eq!(
+            Err(DivideByZeroError::new(Decimal::one().denominator())),
+            Decimal::zero().checked_inv()
+        );
+    }
+
+    #[test]
+    fn fraction_to_permille_works() {
+        assert_eq!(
+            fraction_to_permille(&(1u128, 4u128)).unwrap(),
+            Uint128::new(250)
+        );
+        assert_eq!(
+            fraction_to_permille(&(Uint64::new(3), Uint64::new(3))).unwrap(),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            fraction_to_permille(&(0u128, 7u128)).unwrap(),
+            Uint128::zero()
+        );
+        // rounded down
+        assert_eq!(
+            fraction_to_permille(&(2u128, 3u128)).unwrap(),
+            Uint128::new(666)
+        );
+        assert_eq!(
+            fraction_to_permille(&Decimal::percent(125)).unwrap(),
+            Uint128::new(1250)
+        );
+    }
+
+    #[test]
+    fn fraction_to_permille_errors() {
+        let err = fraction_to_permille(&(1u128, 0u128)).unwrap_err();
+        assert!(matches!(err, CheckedMultiplyFractionError::DivideByZero(_)));
+
+        let err = fraction_to_permille(&(u128::MAX, 1u128)).unwrap_err();
+        assert!(matches!(
+            err,
+            CheckedMultiplyFractionError::ConversionOverflow(_)
+        ));
+    }
 }
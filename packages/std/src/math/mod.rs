@@ -2,15 +2,18 @@ mod decimal;
 mod decimal256;
 mod fraction;
 mod isqrt;
+mod shares;
 mod uint128;
 mod uint256;
 mod uint512;
 mod uint64;
+pub mod uint_hex;
 
 pub use decimal::{Decimal, DecimalRangeExceeded};
 pub use decimal256::{Decimal256, Decimal256RangeExceeded};
-pub use fraction::Fraction;
+pub use fraction::{fraction_to_permille, Fraction};
 pub use isqrt::Isqrt;
+pub use shares::compute_shares;
 pub use uint128::Uint128;
 pub use uint256::Uint256;
 pub use uint512::Uint512;
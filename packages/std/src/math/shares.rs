@@ -0,0 +1,136 @@
+use crate::errors::{StdError, StdResult};
+use crate::{Uint128, Uint256};
+
+/// Splits `total` proportionally to the integer `weights`, returning one share per weight.
+///
+/// Every share is first floored. The units lost to flooring are then handed out one by one
+/// to the shares with the largest remainders (largest-remainder method), with ties going to
+/// the earlier weight. This is deterministic and the shares always sum up to `total`.
+///
+/// Returns an error if `total` is zero or the weights are empty or sum up to zero.
+///
+/// ## Examples
+///
+/// ```
+/// use cosmwasm_std::{compute_shares, Uint128};
+///
+/// let shares = compute_shares(Uint128::new(100), &[2, 3, 2]).unwrap();
+/// assert_eq!(shares, vec![Uint128::new(29), Uint128::new(43), Uint128::new(28)]);
+/// ```
+pub fn compute_shares(total: Uint128, weights: &[u64]) -> StdResult<Vec<Uint128>> {
+    if total.is_zero() {
+        return Err(StdError::generic_err("Total to split must not be zero"));
+    }
+    let weight_sum: u128 = weights.iter().map(|w| *w as u128).sum();
+    if weight_sum == 0 {
+        return Err(StdError::generic_err(
+            "Weights must not be empty or sum up to zero",
+        ));
+    }
+
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (index, weight) in weights.iter().enumerate() {
+        let weight = *weight as u128;
+        // cannot overflow since weight <= weight_sum
+        shares.push(total.mul_floor((weight, weight_sum)));
+        remainders.push((total.full_mul(weight) % Uint256::from(weight_sum), index));
+    }
+
+    // flooring loses less than one unit per share, so this is smaller than the number of shares
+    let distributed = shares
+        .iter()
+        .fold(Uint128::zero(), |sum, share| sum + *share);
+    let missing = (total - distributed).u128() as usize;
+    remainders.sort_by(|(a, a_index), (b, b_index)| b.cmp(a).then(a_index.cmp(b_index)));
+    for (_, index) in remainders.into_iter().take(missing) {
+        shares[index] += Uint128::one();
+    }
+    Ok(shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sum(shares: &[Uint128]) -> Uint128 {
+        shares
+            .iter()
+            .fold(Uint128::zero(), |sum, share| sum + *share)
+    }
+
+    #[test]
+    fn compute_shares_splits_evenly() {
+        let shares = compute_shares(Uint128::new(300), &[1, 1, 1]).unwrap();
+        assert_eq!(shares, vec![Uint128::new(100); 3]);
+
+        let shares = compute_shares(Uint128::new(100), &[1, 3]).unwrap();
+        assert_eq!(shares, vec![Uint128::new(25), Uint128::new(75)]);
+
+        // zero weights get nothing
+        let shares = compute_shares(Uint128::new(100), &[0, 4, 0]).unwrap();
+        assert_eq!(
+            shares,
+            vec![Uint128::zero(), Uint128::new(100), Uint128::zero()]
+        );
+    }
+
+    #[test]
+    fn compute_shares_assigns_remainder_to_largest_remainders() {
+        // 10 * 1/3 = 3.33 for each, the first gets the single missing unit
+        let shares = compute_shares(Uint128::new(10), &[1, 1, 1]).unwrap();
+        assert_eq!(
+            shares,
+            vec![Uint128::new(4), Uint128::new(3), Uint128::new(3)]
+        );
+
+        // 100 * [2, 3, 2] / 7 = [28.57, 42.86, 28.57], two units missing
+        let shares = compute_shares(Uint128::new(100), &[2, 3, 2]).unwrap();
+        assert_eq!(
+            shares,
+            vec![Uint128::new(29), Uint128::new(43), Uint128::new(28)]
+        );
+
+        // 7 * [1, 5] / 6 = [1.17, 5.83]
+        let shares = compute_shares(Uint128::new(7), &[1, 5]).unwrap();
+        assert_eq!(shares, vec![Uint128::new(1), Uint128::new(6)]);
+    }
+
+    #[test]
+    fn compute_shares_sum_equals_total() {
+        let weights: &[&[u64]] = &[
+            &[1],
+            &[1, 2, 3, 4, 5, 6, 7],
+            &[13, 17, 19, 23],
+            &[u64::MAX, u64::MAX, 1],
+            &[999_999, 1, 0, 3],
+        ];
+        for total in [1u128, 7, 1_000, 123_456_789, u128::MAX] {
+            for weights in weights {
+                let shares = compute_shares(Uint128::new(total), weights).unwrap();
+                assert_eq!(shares.len(), weights.len());
+                assert_eq!(sum(&shares), Uint128::new(total));
+            }
+        }
+    }
+
+    #[test]
+    fn compute_shares_rejects_invalid_input() {
+        let err = compute_shares(Uint128::zero(), &[1, 2]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Total to split must not be zero")
+        );
+
+        let err = compute_shares(Uint128::new(100), &[]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Weights must not be empty or sum up to zero")
+        );
+        let err = compute_shares(Uint128::new(100), &[0, 0]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Weights must not be empty or sum up to zero")
+        );
+    }
+}
@@ -12,7 +12,7 @@ use crate::errors::{
     CheckedMultiplyFractionError, CheckedMultiplyRatioError, DivideByZeroError, OverflowError,
     OverflowOperation, StdError,
 };
-use crate::{impl_mul_fraction, ConversionOverflowError, Fraction, Uint256, Uint64};
+use crate::{impl_mul_fraction, ConversionOverflowError, Fraction, Uint256, Uint512, Uint64};
 
 /// A thin wrapper around u128 that is using strings for JSON encoding/decoding,
 /// such that the full u128 range can be used for clients that convert JSON numbers to floats,
@@ -166,6 +166,95 @@ impl Uint128 {
             .ok_or_else(|| OverflowError::new(OverflowOperation::Pow, self, exp))
     }
 
+    /// Like [`Uint128::checked_mul_floor`] but the caller chooses the output type `U`,
+    /// e.g. `Uint256` to keep a result that exceeds the `Uint128` range for further
+    /// computations instead of getting a `ConversionOverflow` error.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use cosmwasm_std::{Uint128, Uint256};
+    /// let fraction = (3u128, 2u128);
+    /// assert!(Uint128::MAX.checked_mul_floor(fraction).is_err());
+    ///
+    /// let res: Uint256 = Uint128::MAX.checked_mul_floor_to(fraction).unwrap();
+    /// assert_eq!(res, Uint256::from(u128::MAX) * Uint256::from(3u32) / Uint256::from(2u32));
+    /// ```
+    pub fn checked_mul_floor_to<U, F, T>(self, rhs: F) -> Result<U, CheckedMultiplyFractionError>
+    where
+        U: TryFrom<Uint256>,
+        CheckedMultiplyFractionError: From<U::Error>,
+        F: Fraction<T>,
+        T: Into<Uint128>,
+    {
+        let divisor = rhs.denominator().into();
+        let res = self
+            .full_mul(rhs.numerator().into())
+            .checked_div(divisor.into())?;
+        Ok(U::try_from(res)?)
+    }
+
+    /// Multiplies `self` by `ratio` raised to the power of `exp`, e.g. to apply
+    /// a per-period interest rate for `exp` periods.
+    ///
+    /// Uses exponentiation by squaring: the ratio is reduced and then squared exactly
+    /// (numerator and denominator separately) as long as both fit into a `Uint256`, and
+    /// `self` is multiplied with a floored fraction multiplication only once per set bit
+    /// of `exp`. Thus the result is rounded down at most `log2(exp) + 1` times, while
+    /// applying the ratio `exp` times via [`Uint128::checked_mul_floor`] rounds down after
+    /// every single step. Once the squared ratio does not fit anymore, numerator and
+    /// denominator are shortened to 255 bits, rounding the ratio down. The result is never
+    /// larger than the exact value, but may be slightly smaller for large exponents.
+    ///
+    /// Returns an error if the denominator is zero or the result does not fit into a `Uint128`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use cosmwasm_std::Uint128;
+    /// let principal = Uint128::new(12345);
+    /// let res = principal.checked_mul_fraction_pow((Uint128::new(3), Uint128::new(2)), 4).unwrap();
+    /// assert_eq!(res, Uint128::new(62496)); // 62496.5625 rounds down
+    ///
+    /// // applying the ratio 4 times rounds down 4 times
+    /// let mut naive = principal;
+    /// for _ in 0..4 {
+    ///     naive = naive.mul_floor((3u128, 2u128));
+    /// }
+    /// assert_eq!(naive, Uint128::new(62493));
+    /// ```
+    pub fn checked_mul_fraction_pow(
+        self,
+        ratio: (Uint128, Uint128),
+        mut exp: u32,
+    ) -> Result<Self, CheckedMultiplyFractionError> {
+        let (numerator, denominator) = ratio;
+        if denominator.is_zero() {
+            return Err(DivideByZeroError::new(self).into());
+        }
+
+        // reducing the ratio keeps its exact powers as small as possible
+        let divisor = gcd(numerator.u128(), denominator.u128());
+        let mut numerator = Uint256::from(numerator.u128() / divisor);
+        let mut denominator = Uint256::from(denominator.u128() / divisor);
+
+        let mut result = self;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                // at most 128 + 256 bits, so this cannot overflow
+                let product = Uint512::from(result).checked_mul(numerator.into())?;
+                result = product.checked_div(denominator.into())?.try_into()?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                let (n, d) = square_ratio(numerator, denominator);
+                numerator = n;
+                denominator = d;
+            }
+        }
+        Ok(result)
+    }
+
     pub fn checked_div(self, other: Self) -> Result<Self, DivideByZeroError> {
         self.0
             .checked_div(other.0)
@@ -234,6 +323,47 @@ impl Uint128 {
 
 impl_mul_fraction!(Uint128);
 
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let remainder = a % b;
+        a = b;
+        b = remainder;
+    }
+    a
+}
+
+/// Number of significant bits of `value`
+fn bit_length(value: Uint512) -> u32 {
+    let bytes = value.to_be_bytes();
+    match bytes.iter().position(|&byte| byte != 0) {
+        Some(index) => (64 - index as u32) * 8 - bytes[index].leading_zeros(),
+        None => 0,
+    }
+}
+
+/// Squares the ratio `numerator / denominator`. If the square does not fit into
+/// 256 bits, both parts are shortened to 255 bits such that the returned ratio is
+/// never larger than the exact square (the numerator is rounded down and the
+/// denominator up).
+fn square_ratio(numerator: Uint256, denominator: Uint256) -> (Uint256, Uint256) {
+    let numerator = numerator.full_mul(numerator);
+    let denominator = denominator.full_mul(denominator);
+    let bits = bit_length(numerator).max(bit_length(denominator));
+    let (numerator, denominator) = if bits > 256 {
+        let shift = bits - 255;
+        (
+            numerator >> shift,
+            ((denominator - Uint512::one()) >> shift) + Uint512::one(),
+        )
+    } else {
+        (numerator, denominator)
+    };
+    (
+        numerator.try_into().expect("fits into 256 bits"),
+        denominator.try_into().expect("fits into 256 bits"),
+    )
+}
+
 // `From<u{128,64,32,16,8}>` is implemented manually instead of
 // using `impl<T: Into<u128>> From<T> for Uint128` because
 // of the conflict with `TryFrom<&str>` as described here
@@ -1046,6 +1176,175 @@ mod tests {
         }
     }
 
+    #[test]
+    fn checked_mul_floor_to_works() {
+        let fraction = (8u128, 21u128);
+        let res: Uint128 = Uint128::new(123456).checked_mul_floor_to(fraction).unwrap();
+        assert_eq!(res, Uint128::new(47030));
+        let res: Uint256 = Uint128::new(123456).checked_mul_floor_to(fraction).unwrap();
+        assert_eq!(res, Uint256::from(47030u32));
+        let res: Uint64 = Uint128::new(123456).checked_mul_floor_to(fraction).unwrap();
+        assert_eq!(res, Uint64::new(47030));
+    }
+
+    #[test]
+    fn checked_mul_floor_to_avoids_narrow_overflow() {
+        // the narrow variant fails
+        let fraction = (Uint128::MAX, Uint128::new(7));
+        assert!(matches!(
+            Uint128::MAX.checked_mul_floor(fraction),
+            Err(ConversionOverflow(_))
+        ));
+        assert!(matches!(
+            Uint128::MAX.checked_mul_floor_to::<Uint128, _, _>(fraction),
+            Err(ConversionOverflow(_))
+        ));
+
+        // the wide variant succeeds
+        let res: Uint256 = Uint128::MAX.checked_mul_floor_to(fraction).unwrap();
+        assert_eq!(
+            res,
+            Uint256::from(u128::MAX) * Uint256::from(u128::MAX) / Uint256::from(7u32)
+        );
+
+        // and can be scaled back down
+        let scaled: Uint128 = (res / Uint256::from(u128::MAX)).try_into().unwrap();
+        assert_eq!(scaled, Uint128::MAX / Uint128::new(7));
+
+        // errors other than conversion are still reported
+        let fraction = (Uint128::MAX, Uint128::zero());
+        assert!(matches!(
+            Uint128::MAX.checked_mul_floor_to::<Uint256, _, _>(fraction),
+            Err(DivideByZero(_))
+        ));
+    }
+
+    #[test]
+    fn checked_mul_fraction_pow_works() {
+        let ratio = |n: u128, d: u128| (Uint128::new(n), Uint128::new(d));
+
+        // exponent 0 and 1
+        assert_eq!(
+            Uint128::new(123).checked_mul_fraction_pow(ratio(3, 2), 0),
+            Ok(Uint128::new(123))
+        );
+        assert_eq!(
+            Uint128::new(123).checked_mul_fraction_pow(ratio(3, 2), 1),
+            Ok(Uint128::new(184))
+        );
+
+        // exact results are the same as applying the ratio repeatedly
+        assert_eq!(
+            Uint128::new(1000).checked_mul_fraction_pow(ratio(3, 2), 3),
+            Ok(Uint128::new(3375))
+        );
+        assert_eq!(
+            Uint128::new(100).checked_mul_fraction_pow(ratio(2, 3), 3),
+            Ok(Uint128::new(29))
+        );
+        assert_eq!(
+            Uint128::zero().checked_mul_fraction_pow(ratio(5, 4), 7),
+            Ok(Uint128::zero())
+        );
+    }
+
+    #[test]
+    fn checked_mul_fraction_pow_rounds_less_than_naive() {
+        fn naive(value: u128, ratio: (u128, u128), exp: u32) -> Uint128 {
+            let mut value = Uint128::new(value);
+            for _ in 0..exp {
+                value = value.mul_floor(ratio);
+            }
+            value
+        }
+
+        // (value, ratio, exp, expected, naive)
+        let cases = [
+            (12345, (3, 2), 2, 27776, 27775),
+            (12345, (3, 2), 3, 41663, 41662), // exact: 41664.375
+            (12345, (3, 2), 4, 62496, 62493),
+            (1000, (5, 4), 3, 1953, 1952),
+            (100, (9, 10), 4, 65, 64),
+            (12345, (11, 10), 4, 18074, 18071),
+        ];
+        for (value, (n, d), exp, expected, expected_naive) in cases {
+            let res = Uint128::new(value)
+                .checked_mul_fraction_pow((Uint128::new(n), Uint128::new(d)), exp)
+                .unwrap();
+            assert_eq!(res, Uint128::new(expected));
+            assert_eq!(naive(value, (n, d), exp), Uint128::new(expected_naive));
+        }
+
+        // squared ratio does not fit anymore, so it gets rounded
+        let principal = Uint128::new(1_000_000_000_000_000_000);
+        let daily = (Uint128::new(1_000_001), Uint128::new(1_000_000));
+        let res = principal.checked_mul_fraction_pow(daily, 365).unwrap();
+        assert_eq!(res, Uint128::new(1_000_365_066_438_038_756)); // exact: ...757.3
+        assert_eq!(
+            naive(1_000_000_000_000_000_000, (1_000_001, 1_000_000), 365),
+            Uint128::new(1_000_365_066_438_038_625)
+        );
+    }
+
+    #[test]
+    fn checked_mul_fraction_pow_only_fails_if_result_overflows() {
+        // the squared ratio exceeds 128 bits long before the result does
+        let ratio = (Uint128::new(10u128.pow(20)), Uint128::new(10u128.pow(18)));
+        let res = Uint128::one().checked_mul_fraction_pow(ratio, 16).unwrap();
+        assert_eq!(res, Uint128::new(10u128.pow(32)));
+        let res = Uint128::one().checked_mul_fraction_pow(ratio, 19).unwrap();
+        assert_eq!(res, Uint128::new(10u128.pow(38)));
+        assert!(matches!(
+            Uint128::one().checked_mul_fraction_pow(ratio, 20),
+            Err(ConversionOverflow(_))
+        ));
+
+        // the ratio gets shortened to 255 bits, but the result stays close to exact
+        let per_second = (
+            Uint128::new(1_000_000_000_000_000_001),
+            Uint128::new(1_000_000_000_000_000_000),
+        );
+        let res = Uint128::new(1_000_000_000_000_000_000)
+            .checked_mul_fraction_pow(per_second, u32::MAX)
+            .unwrap();
+        assert_eq!(res, Uint128::new(1_000_000_004_294_967_302)); // exact: ...304.2
+
+        // tiny ratios round down to zero instead of failing
+        let ratio = (Uint128::new(3), Uint128::new(10u128.pow(30)));
+        let res = Uint128::new(1_000_000)
+            .checked_mul_fraction_pow(ratio, 5)
+            .unwrap();
+        assert_eq!(res, Uint128::zero());
+    }
+
+    #[test]
+    fn checked_mul_fraction_pow_does_not_panic_on_overflow() {
+        let ratio = (Uint128::new(3), Uint128::new(2));
+        assert_eq!(
+            Uint128::MAX.checked_mul_fraction_pow(ratio, 1),
+            Err(ConversionOverflow(ConversionOverflowError {
+                source_type: "Uint512",
+                target_type: "Uint128",
+                value: "510423550381407695195061911147652317182".to_string()
+            })),
+        );
+        assert!(matches!(
+            Uint128::new(1u128 << 100).checked_mul_fraction_pow(ratio, 200),
+            Err(ConversionOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn checked_mul_fraction_pow_does_not_panic_on_zero_div() {
+        let ratio = (Uint128::new(3), Uint128::zero());
+        assert_eq!(
+            Uint128::new(123).checked_mul_fraction_pow(ratio, 2),
+            Err(DivideByZero(DivideByZeroError {
+                operand: "123".to_string()
+            })),
+        );
+    }
+
     #[test]
     fn mul_floor_works_with_zero() {
         let fraction = (Uint128::zero(), Uint128::new(21));
@@ -1091,6 +1390,23 @@ mod tests {
         assert_eq!(Uint128::new(47030), res)
     }
 
+    #[test]
+    fn mul_floor_works_with_decimal_at_max() {
+        // the Decimal denominator of 10^18 takes the intermediate product far above Uint128::MAX
+        let res = Uint128::MAX.mul_floor(Decimal::one());
+        assert_eq!(Uint128::MAX, res);
+        assert_eq!(
+            Uint128::MAX.checked_mul_floor(Decimal::one()),
+            Ok(Uint128::MAX)
+        );
+
+        let res = Uint128::MAX.mul_floor(Decimal::percent(99)); // 336_879_543_251_729_078_828_740_861_357_450_529_340.45
+        assert_eq!(
+            Uint128::new(336_879_543_251_729_078_828_740_861_357_450_529_340),
+            res
+        );
+    }
+
     #[test]
     #[should_panic(expected = "ConversionOverflowError")]
     fn mul_floor_panics_on_overflow() {
@@ -1212,6 +1528,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn try_mul_floor_and_ceil_work() {
+        let fraction = (8u128, 21u128);
+        assert_eq!(
+            Uint128::new(123456).try_mul_floor(fraction),
+            Ok(Uint128::new(47030))
+        );
+        assert_eq!(
+            Uint128::new(123456).try_mul_ceil(fraction),
+            Ok(Uint128::new(47031))
+        );
+    }
+
+    #[test]
+    fn try_mul_floor_and_ceil_return_error_on_overflow() {
+        let fraction = (21u128, 8u128);
+        let expected = || {
+            StdError::from(ConversionOverflowError {
+                source_type: "Uint256",
+                target_type: "Uint128",
+                value: "893241213167463466591358344508391555069".to_string(),
+            })
+        };
+        assert_eq!(Uint128::MAX.try_mul_floor(fraction), Err(expected()));
+        assert_eq!(Uint128::MAX.try_mul_ceil(fraction), Err(expected()));
+    }
+
+    #[test]
+    fn try_mul_floor_and_ceil_return_error_on_zero_div() {
+        let fraction = (21u128, 0u128);
+        assert!(matches!(
+            Uint128::new(123456).try_mul_floor(fraction),
+            Err(StdError::DivideByZero { .. })
+        ));
+        assert!(matches!(
+            Uint128::new(123456).try_mul_ceil(fraction),
+            Err(StdError::DivideByZero { .. })
+        ));
+    }
+
     #[test]
     #[should_panic(expected = "DivideByZeroError")]
     fn div_floor_raises_with_zero() {
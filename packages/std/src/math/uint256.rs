@@ -334,6 +334,26 @@ impl Uint256 {
             self - other
         }
     }
+
+    /// Converts to `Uint128`, returning an error if the value is too large.
+    pub fn try_into_uint128(self) -> Result<Uint128, ConversionOverflowError> {
+        self.try_into()
+    }
+
+    /// Converts to `Uint128`, clamping values that are too large to `Uint128::MAX`.
+    pub fn to_uint128_saturating(self) -> Uint128 {
+        self.try_into_uint128().unwrap_or(Uint128::MAX)
+    }
+
+    /// Converts to `Uint64`, returning an error if the value is too large.
+    pub fn try_into_uint64(self) -> Result<Uint64, ConversionOverflowError> {
+        self.try_into()
+    }
+
+    /// Converts to `Uint64`, clamping values that are too large to `Uint64::MAX`.
+    pub fn to_uint64_saturating(self) -> Uint64 {
+        self.try_into_uint64().unwrap_or(Uint64::MAX)
+    }
 }
 
 impl_mul_fraction!(Uint256);
@@ -390,6 +410,16 @@ impl TryFrom<Uint256> for Uint128 {
     }
 }
 
+impl TryFrom<Uint256> for Uint64 {
+    type Error = ConversionOverflowError;
+
+    fn try_from(value: Uint256) -> Result<Self, Self::Error> {
+        Ok(Uint64::new(value.0.try_into().map_err(|_| {
+            ConversionOverflowError::new("Uint256", "Uint64", value.to_string())
+        })?))
+    }
+}
+
 impl TryFrom<&str> for Uint256 {
     type Error = StdError;
 
@@ -1069,6 +1099,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn uint256_try_into_uint128_works() {
+        assert_eq!(
+            Uint256::from(42u128).try_into_uint128(),
+            Ok(Uint128::new(42))
+        );
+        assert_eq!(
+            Uint256::from(u128::MAX).try_into_uint128(),
+            Ok(Uint128::MAX)
+        );
+        assert_eq!(
+            (Uint256::from(u128::MAX) + Uint256::one()).try_into_uint128(),
+            Err(ConversionOverflowError::new(
+                "Uint256",
+                "Uint128",
+                "340282366920938463463374607431768211456"
+            ))
+        );
+    }
+
+    #[test]
+    fn uint256_to_uint128_saturating_works() {
+        assert_eq!(
+            Uint256::from(42u128).to_uint128_saturating(),
+            Uint128::new(42)
+        );
+        assert_eq!(
+            Uint256::from(u128::MAX).to_uint128_saturating(),
+            Uint128::MAX
+        );
+        assert_eq!(Uint256::MAX.to_uint128_saturating(), Uint128::MAX);
+    }
+
+    #[test]
+    fn uint256_try_into_uint64_works() {
+        assert_eq!(Uint256::from(42u128).try_into_uint64(), Ok(Uint64::new(42)));
+        assert_eq!(Uint256::from(u64::MAX).try_into_uint64(), Ok(Uint64::MAX));
+        assert_eq!(
+            Uint256::from(u128::MAX).try_into_uint64(),
+            Err(ConversionOverflowError::new(
+                "Uint256",
+                "Uint64",
+                u128::MAX.to_string()
+            ))
+        );
+        let target = Uint64::try_from(Uint256::MAX);
+        assert_eq!(
+            target,
+            Err(ConversionOverflowError::new(
+                "Uint256",
+                "Uint64",
+                Uint256::MAX.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn uint256_to_uint64_saturating_works() {
+        assert_eq!(
+            Uint256::from(42u128).to_uint64_saturating(),
+            Uint64::new(42)
+        );
+        assert_eq!(Uint256::from(u128::MAX).to_uint64_saturating(), Uint64::MAX);
+        assert_eq!(Uint256::MAX.to_uint64_saturating(), Uint64::MAX);
+    }
+
     #[test]
     fn uint256_from_u128() {
         assert_eq!(
@@ -1723,6 +1819,26 @@ mod tests {
         assert_eq!(Uint256::from(47030u32), res)
     }
 
+    #[test]
+    fn mul_floor_works_with_decimal256_at_max() {
+        // the Decimal256 denominator of 10^18 takes the intermediate product far above Uint256::MAX
+        let res = Uint256::MAX.mul_floor(Decimal256::one());
+        assert_eq!(Uint256::MAX, res);
+        assert_eq!(
+            Uint256::MAX.checked_mul_floor(Decimal256::one()),
+            Ok(Uint256::MAX)
+        );
+
+        let res = Uint256::MAX.mul_floor(Decimal256::percent(99));
+        assert_eq!(
+            Uint256::from_str(
+                "114634168344943033469335275158601028774737284818984158399063008167833998343535"
+            )
+            .unwrap(),
+            res
+        );
+    }
+
     #[test]
     #[should_panic(expected = "ConversionOverflowError")]
     fn mul_floor_panics_on_overflow() {
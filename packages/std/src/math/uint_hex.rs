@@ -0,0 +1,216 @@
+//! Opt-in serialization of [`Uint128`] and [`Uint256`] as fixed-width hex strings,
+//! as expected by systems like Ethereum bridges.
+//!
+//! Values are serialized as a `0x` prefixed, zero padded, lowercase hex string of the
+//! type's full byte width (32 digits for `Uint128`, 64 digits for `Uint256`).
+//! For deserialization, both `0x` prefixed hex strings of up to this width and the
+//! canonical decimal strings are accepted.
+//!
+//! # Examples
+//!
+//! ```
+//! # use cosmwasm_std::{to_vec, Uint256};
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! struct Deposit {
+//!     #[serde(with = "cosmwasm_std::uint_hex")]
+//!     amount: Uint256,
+//! }
+//!
+//! let json = to_vec(&Deposit { amount: Uint256::from(255u32) }).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(json).unwrap(),
+//!     r#"{"amount":"0x00000000000000000000000000000000000000000000000000000000000000ff"}"#
+//! );
+//! ```
+
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+use crate::errors::StdError;
+use crate::{Uint128, Uint256};
+
+/// An unsigned integer type with a fixed width big endian byte representation,
+/// which can be (de)serialized with this module.
+pub trait HexUint: FromStr<Err = StdError> {
+    /// Number of bytes in the big endian representation
+    const BYTE_LEN: usize;
+
+    fn to_be_vec(&self) -> Vec<u8>;
+
+    /// Creates the value from exactly `BYTE_LEN` big endian bytes
+    fn from_be_slice(data: &[u8]) -> Self;
+}
+
+impl HexUint for Uint128 {
+    const BYTE_LEN: usize = 16;
+
+    fn to_be_vec(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn from_be_slice(data: &[u8]) -> Self {
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(data);
+        Uint128::new(u128::from_be_bytes(bytes))
+    }
+}
+
+impl HexUint for Uint256 {
+    const BYTE_LEN: usize = 32;
+
+    fn to_be_vec(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn from_be_slice(data: &[u8]) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(data);
+        Uint256::from_be_bytes(bytes)
+    }
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: HexUint,
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("0x{}", hex::encode(value.to_be_vec())))
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: HexUint,
+    D: Deserializer<'de>,
+{
+    let input = String::deserialize(deserializer)?;
+    parse(&input).map_err(de::Error::custom)
+}
+
+fn parse<T: HexUint>(input: &str) -> Result<T, StdError> {
+    let digits = match input.strip_prefix("0x") {
+        Some(digits) => digits,
+        None => return T::from_str(input),
+    };
+    let width = T::BYTE_LEN * 2;
+    if digits.is_empty() || digits.len() > width {
+        return Err(StdError::generic_err(format!(
+            "Parsing hex: expected 1 to {} digits after 0x, got {}",
+            width,
+            digits.len()
+        )));
+    }
+    let padded = format!("{:0>width$}", digits, width = width);
+    let bytes =
+        hex::decode(padded).map_err(|e| StdError::generic_err(format!("Parsing hex: {}", e)))?;
+    Ok(T::from_be_slice(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_slice, to_vec};
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Amounts {
+        #[serde(with = "crate::uint_hex")]
+        small: Uint128,
+        #[serde(with = "crate::uint_hex")]
+        large: Uint256,
+    }
+
+    #[test]
+    fn uint_hex_serializes_fixed_width() {
+        let amounts = Amounts {
+            small: Uint128::new(0x1234),
+            large: Uint256::MAX,
+        };
+        let json = String::from_utf8(to_vec(&amounts).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"small":"0x{}1234","large":"0x{}"}}"#,
+                "0".repeat(28),
+                "f".repeat(64)
+            )
+        );
+
+        // zero is padded as well
+        let amounts = Amounts {
+            small: Uint128::zero(),
+            large: Uint256::zero(),
+        };
+        let json = String::from_utf8(to_vec(&amounts).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"small":"0x{}","large":"0x{}"}}"#,
+                "0".repeat(32),
+                "0".repeat(64)
+            )
+        );
+    }
+
+    #[test]
+    fn uint_hex_round_trips() {
+        for amounts in [
+            Amounts {
+                small: Uint128::zero(),
+                large: Uint256::zero(),
+            },
+            Amounts {
+                small: Uint128::new(123456789),
+                large: Uint256::from(987654321u128),
+            },
+            Amounts {
+                small: Uint128::MAX,
+                large: Uint256::MAX,
+            },
+        ] {
+            let serialized = to_vec(&amounts).unwrap();
+            let deserialized: Amounts = from_slice(&serialized).unwrap();
+            assert_eq!(deserialized, amounts);
+        }
+    }
+
+    #[test]
+    fn uint_hex_deserializes_short_and_uppercase_hex() {
+        let amounts: Amounts = from_slice(br#"{"small":"0xff","large":"0xABC"}"#).unwrap();
+        assert_eq!(
+            amounts,
+            Amounts {
+                small: Uint128::new(255),
+                large: Uint256::from(0xabcu32),
+            }
+        );
+    }
+
+    #[test]
+    fn uint_hex_deserializes_decimal() {
+        let amounts: Amounts =
+            from_slice(br#"{"small":"340282366920938463463374607431768211455","large":"42"}"#)
+                .unwrap();
+        assert_eq!(
+            amounts,
+            Amounts {
+                small: Uint128::MAX,
+                large: Uint256::from(42u32),
+            }
+        );
+    }
+
+    #[test]
+    fn uint_hex_rejects_invalid_input() {
+        // too wide
+        let json = format!(r#"{{"small":"0x1{}","large":"0"}}"#, "0".repeat(32));
+        from_slice::<Amounts>(json.as_bytes()).unwrap_err();
+        // no digits
+        from_slice::<Amounts>(br#"{"small":"0x","large":"0"}"#).unwrap_err();
+        // not hex
+        from_slice::<Amounts>(br#"{"small":"0xzz","large":"0"}"#).unwrap_err();
+        // not decimal
+        from_slice::<Amounts>(br#"{"small":"ff","large":"0"}"#).unwrap_err();
+    }
+}
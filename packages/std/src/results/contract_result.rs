@@ -59,6 +59,19 @@ impl<S> ContractResult<S> {
     pub fn is_err(&self) -> bool {
         matches!(self, ContractResult::Err(_))
     }
+
+    /// Maps a `ContractResult<S>` to `ContractResult<T>` by applying a function to an
+    /// `Ok` value, leaving an `Err` value untouched.
+    pub fn map<T, F: FnOnce(S) -> T>(self, op: F) -> ContractResult<T> {
+        self.into_result().map(op).into()
+    }
+
+    /// Calls `op` if the result is `Ok`, otherwise returns the `Err` value of `self`.
+    pub fn and_then<T, F: FnOnce(S) -> ContractResult<T>>(self, op: F) -> ContractResult<T> {
+        self.into_result()
+            .and_then(|value| op(value).into_result())
+            .into()
+    }
 }
 
 impl<S: fmt::Debug> ContractResult<S> {
@@ -108,6 +121,60 @@ mod tests {
         assert_eq!(&to_vec(&result).unwrap(), b"{\"error\":\"broken\"}");
     }
 
+    #[test]
+    fn contract_result_map_works() {
+        let result: ContractResult<u64> = ContractResult::Ok(21);
+        assert_eq!(result.map(|v| v * 2), ContractResult::Ok(42));
+
+        let result: ContractResult<u64> = ContractResult::Err("broken".to_string());
+        assert_eq!(
+            result.map(|v| v * 2),
+            ContractResult::Err("broken".to_string())
+        );
+
+        // the type can change
+        let result: ContractResult<u64> = ContractResult::Ok(21);
+        assert_eq!(
+            result.map(|v| v.to_string()),
+            ContractResult::Ok("21".to_string())
+        );
+    }
+
+    #[test]
+    fn contract_result_and_then_works() {
+        let half = |v: u64| -> ContractResult<u64> {
+            if v % 2 == 0 {
+                ContractResult::Ok(v / 2)
+            } else {
+                ContractResult::Err("odd".to_string())
+            }
+        };
+
+        let result: ContractResult<u64> = ContractResult::Ok(42);
+        assert_eq!(result.and_then(half), ContractResult::Ok(21));
+
+        let result: ContractResult<u64> = ContractResult::Ok(21);
+        assert_eq!(
+            result.and_then(half),
+            ContractResult::Err("odd".to_string())
+        );
+
+        let result: ContractResult<u64> = ContractResult::Err("broken".to_string());
+        assert_eq!(
+            result.and_then(half),
+            ContractResult::Err("broken".to_string())
+        );
+    }
+
+    #[test]
+    fn contract_result_into_result_works() {
+        let result: ContractResult<u64> = ContractResult::Ok(42);
+        assert_eq!(result.into_result(), Ok(42));
+
+        let result: ContractResult<u64> = ContractResult::Err("broken".to_string());
+        assert_eq!(result.into_result(), Err("broken".to_string()));
+    }
+
     #[test]
     fn contract_result_deserialization_works() {
         let result: ContractResult<u64> = from_slice(br#"{"ok":12}"#).unwrap();
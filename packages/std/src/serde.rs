@@ -12,6 +12,13 @@ pub fn from_slice<T: DeserializeOwned>(value: &[u8]) -> StdResult<T> {
     serde_json_wasm::from_slice(value).map_err(|e| StdError::parse_err(type_name::<T>(), e))
 }
 
+/// Like [`from_slice`] but uses `name` instead of the Rust type name as the target type
+/// of a parse error. This allows for errors like "Error parsing into type WhoAmIResponse ack"
+/// instead of the full path of a generic wrapper type.
+pub fn from_slice_named<T: DeserializeOwned>(value: &[u8], name: &str) -> StdResult<T> {
+    serde_json_wasm::from_slice(value).map_err(|e| StdError::parse_err(name, e))
+}
+
 pub fn from_binary<T: DeserializeOwned>(value: &Binary) -> StdResult<T> {
     from_slice(value.as_slice())
 }
@@ -89,6 +96,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_slice_named_works() {
+        let deserialized: SomeMsg = from_slice_named(br#"{"refund":{}}"#, "SomeMsg").unwrap();
+        assert_eq!(deserialized, SomeMsg::Refund {});
+
+        // the name is used in errors instead of the type name
+        let err = from_slice_named::<SomeMsg>(br#"{"cowsay":{}}"#, "cowsay msg").unwrap_err();
+        match err {
+            StdError::ParseErr {
+                target_type, msg, ..
+            } => {
+                assert_eq!(target_type, "cowsay msg");
+                assert!(msg.contains("text"), "unexpected message: {}", msg);
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+        assert!(from_slice_named::<SomeMsg>(b"{", "SomeMsg")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Error parsing into type SomeMsg: "));
+
+        // unlike from_slice
+        let err = from_slice::<SomeMsg>(br#"{"cowsay":{}}"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("cosmwasm_std::serde::tests::SomeMsg"));
+    }
+
     #[test]
     fn from_slice_or_binary() {
         let msg = SomeMsg::Refund {};
@@ -1,6 +1,8 @@
 mod bucket;
 mod length_prefixed;
 mod namespace_helpers;
+#[cfg(feature = "iterator")]
+mod pagination;
 mod prefixed_storage;
 mod sequence;
 mod singleton;
@@ -8,6 +10,8 @@ mod type_helpers;
 
 pub use bucket::{bucket, bucket_read, Bucket, ReadonlyBucket};
 pub use length_prefixed::{to_length_prefixed, to_length_prefixed_nested};
+#[cfg(feature = "iterator")]
+pub use pagination::{paginate, take_limited, DEFAULT_LIMIT, MAX_LIMIT};
 pub use prefixed_storage::{prefixed, prefixed_read, PrefixedStorage, ReadonlyPrefixedStorage};
 pub use sequence::{currval, nextval, sequence};
 pub use singleton::{singleton, singleton_read, ReadonlySingleton, Singleton};
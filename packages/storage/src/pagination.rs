@@ -0,0 +1,210 @@
+use serde::{de::DeserializeOwned, ser::Serialize};
+
+use cosmwasm_std::{Order, Record, StdError, StdResult};
+
+use crate::ReadonlyBucket;
+
+/// Number of records returned by `paginate` if no limit is given
+pub const DEFAULT_LIMIT: u32 = 10;
+/// Upper bound for the limit of `paginate`. Larger limits are capped to this.
+pub const MAX_LIMIT: u32 = 30;
+
+/// Returns one page of records from the bucket in the given order.
+///
+/// `start_after` is an exclusive bound: the record with this key is never returned.
+/// For ascending order it is the lower bound, for descending order it is the upper bound,
+/// such that the last key of one page can be passed in to get the next page.
+/// At most `limit` records are returned, which defaults to `DEFAULT_LIMIT` and is
+/// capped to `MAX_LIMIT`.
+pub fn paginate<T>(
+    bucket: &ReadonlyBucket<T>,
+    start_after: Option<&[u8]>,
+    limit: Option<u32>,
+    order: Order,
+) -> StdResult<Vec<Record<T>>>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // range start is inclusive, so we start at the first possible key after `start_after`
+    let exclusive_start = start_after.map(|key| {
+        let mut next = key.to_vec();
+        next.push(0);
+        next
+    });
+    let (start, end) = match order {
+        Order::Ascending => (exclusive_start.as_deref(), None),
+        // range end is exclusive already
+        Order::Descending => (None, start_after),
+    };
+    bucket.range(start, end, order).take(limit).collect()
+}
+
+/// Collects all items of a (range) iterator, returning an error if there are more than `max`.
+///
+/// Unlike `take`, this never truncates silently, such that unexpectedly large collections
+/// are detected. At most `max + 1` items are read from the iterator.
+pub fn take_limited<T, I>(iter: I, max: usize) -> StdResult<Vec<T>>
+where
+    I: IntoIterator<Item = StdResult<T>>,
+{
+    let mut items = Vec::new();
+    for item in iter {
+        if items.len() == max {
+            return Err(StdError::generic_err(format!(
+                "Range exceeds the limit of {} elements",
+                max
+            )));
+        }
+        items.push(item?);
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bucket;
+    use cosmwasm_std::testing::MockStorage;
+
+    fn setup(store: &mut MockStorage, count: u32) {
+        let mut bucket = bucket::<u32>(store, b"data");
+        for i in 0..count {
+            let key = format!("key-{:03}", i);
+            bucket.save(key.as_bytes(), &i).unwrap();
+        }
+    }
+
+    fn values(records: Vec<Record<u32>>) -> Vec<u32> {
+        records.into_iter().map(|(_, value)| value).collect()
+    }
+
+    #[test]
+    fn paginate_uses_default_limit() {
+        let mut store = MockStorage::new();
+        setup(&mut store, 50);
+        let bucket = ReadonlyBucket::<u32>::new(&store, b"data");
+
+        let page = paginate(&bucket, None, None, Order::Ascending).unwrap();
+        assert_eq!(page.len(), DEFAULT_LIMIT as usize);
+        assert_eq!(values(page), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn paginate_uses_explicit_limit() {
+        let mut store = MockStorage::new();
+        setup(&mut store, 50);
+        let bucket = ReadonlyBucket::<u32>::new(&store, b"data");
+
+        let page = paginate(&bucket, None, Some(3), Order::Ascending).unwrap();
+        assert_eq!(values(page), vec![0, 1, 2]);
+
+        let page = paginate(&bucket, None, Some(3), Order::Descending).unwrap();
+        assert_eq!(values(page), vec![49, 48, 47]);
+
+        // limit is capped
+        let page = paginate(&bucket, None, Some(1000), Order::Ascending).unwrap();
+        assert_eq!(page.len(), MAX_LIMIT as usize);
+    }
+
+    #[test]
+    fn paginate_start_after_is_exclusive() {
+        let mut store = MockStorage::new();
+        setup(&mut store, 10);
+        let bucket = ReadonlyBucket::<u32>::new(&store, b"data");
+
+        let page = paginate(
+            &bucket,
+            Some(b"key-003".as_slice()),
+            Some(2),
+            Order::Ascending,
+        )
+        .unwrap();
+        assert_eq!(page[0].0, b"key-004".to_vec());
+        assert_eq!(values(page), vec![4, 5]);
+
+        let page = paginate(
+            &bucket,
+            Some(b"key-003".as_slice()),
+            Some(2),
+            Order::Descending,
+        )
+        .unwrap();
+        assert_eq!(page[0].0, b"key-002".to_vec());
+        assert_eq!(values(page), vec![2, 1]);
+
+        // walking through all pages returns every record once
+        let mut all = vec![];
+        let mut start_after: Option<Vec<u8>> = None;
+        loop {
+            let page =
+                paginate(&bucket, start_after.as_deref(), Some(3), Order::Ascending).unwrap();
+            match page.last() {
+                Some((key, _)) => start_after = Some(key.clone()),
+                None => break,
+            }
+            all.extend(values(page));
+        }
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn paginate_handles_empty_results() {
+        let mut store = MockStorage::new();
+        let bucket = ReadonlyBucket::<u32>::new(&store, b"data");
+        let page = paginate(&bucket, None, None, Order::Ascending).unwrap();
+        assert!(page.is_empty());
+
+        setup(&mut store, 5);
+        let bucket = ReadonlyBucket::<u32>::new(&store, b"data");
+        let page = paginate(&bucket, Some(b"key-004".as_slice()), None, Order::Ascending).unwrap();
+        assert!(page.is_empty());
+        let page = paginate(
+            &bucket,
+            Some(b"key-000".as_slice()),
+            None,
+            Order::Descending,
+        )
+        .unwrap();
+        assert!(page.is_empty());
+    }
+
+    #[test]
+    fn take_limited_works_under_cap() {
+        let mut store = MockStorage::new();
+        setup(&mut store, 5);
+        let bucket = ReadonlyBucket::<u32>::new(&store, b"data");
+
+        let all = take_limited(bucket.range(None, None, Order::Ascending), 10).unwrap();
+        assert_eq!(values(all), vec![0, 1, 2, 3, 4]);
+
+        // exactly at the cap
+        let all = take_limited(bucket.range(None, None, Order::Descending), 5).unwrap();
+        assert_eq!(values(all), vec![4, 3, 2, 1, 0]);
+
+        // works with any item type
+        let all = take_limited(vec![Ok("a"), Ok("b")], 2).unwrap();
+        assert_eq!(all, vec!["a", "b"]);
+        assert!(take_limited(Vec::<StdResult<u8>>::new(), 0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn take_limited_errors_above_cap() {
+        let mut store = MockStorage::new();
+        setup(&mut store, 5);
+        let bucket = ReadonlyBucket::<u32>::new(&store, b"data");
+
+        let err = take_limited(bucket.range(None, None, Order::Ascending), 4).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Range exceeds the limit of 4 elements")
+        );
+
+        // errors of the items are passed through
+        let items = vec![Ok(1u8), Err(StdError::not_found("u8")), Ok(3u8)];
+        let err = take_limited(items, 10).unwrap_err();
+        assert_eq!(err, StdError::not_found("u8"));
+    }
+}